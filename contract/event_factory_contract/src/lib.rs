@@ -64,6 +64,8 @@ impl EventFactoryContract {
         }
 
         let wasm_hash: BytesN<32> = e.storage().instance().get(&DataKey::EventWasmHash).unwrap();
+        // The factory admin is the platform authority of every event
+        let platform: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
 
         let counter: u32 = e
             .storage()
@@ -93,6 +95,7 @@ impl EventFactoryContract {
             vec![
                 &e,
                 organizer.to_val(),
+                platform.to_val(),
                 name.into_val(&e),
                 symbol.into_val(&e),
                 uri.into_val(&e),
@@ -0,0 +1,119 @@
+/// enumeration.rs — Ticket indexes for holder and tier queries
+///
+/// Each index is a dense list of token IDs, packed `BUCKET_SIZE` to a key so
+/// that a batch of mints only touches a few ledger entries:
+///
+///   IndexLen(index)               -> number of entries
+///   IndexEntries(index, b)        -> entries [b * BUCKET_SIZE, (b + 1) * BUCKET_SIZE)
+///   IndexSlots(index, id / BUCKET_SIZE) -> token ID => position, per ID range
+///
+/// Removal swaps the last entry into the freed slot, so order is not stable:
+/// pages are a snapshot, not a cursor.
+use soroban_sdk::{Env, Map, Vec};
+
+use crate::storage_types::{DataKey, TicketIndex};
+
+const BUCKET_SIZE: u32 = 64;
+
+pub fn len(e: &Env, index: &TicketIndex) -> u32 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::IndexLen(index.clone()))
+        .unwrap_or(0)
+}
+
+fn entries(e: &Env, index: &TicketIndex, bucket: u32) -> Vec<u32> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::IndexEntries(index.clone(), bucket))
+        .unwrap_or_else(|| Vec::new(e))
+}
+
+fn set_entries(e: &Env, index: &TicketIndex, bucket: u32, entries: &Vec<u32>) {
+    let key = DataKey::IndexEntries(index.clone(), bucket);
+    if entries.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, entries);
+    }
+}
+
+fn slots(e: &Env, index: &TicketIndex, token_id: u32) -> Map<u32, u32> {
+    e.storage()
+        .persistent()
+        .get(&DataKey::IndexSlots(index.clone(), token_id / BUCKET_SIZE))
+        .unwrap_or_else(|| Map::new(e))
+}
+
+fn set_slots(e: &Env, index: &TicketIndex, token_id: u32, slots: &Map<u32, u32>) {
+    let key = DataKey::IndexSlots(index.clone(), token_id / BUCKET_SIZE);
+    if slots.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, slots);
+    }
+}
+
+/// Append `token_id` to `index`.
+pub fn add(e: &Env, index: &TicketIndex, token_id: u32) {
+    let position = len(e, index);
+
+    let mut bucket = entries(e, index, position / BUCKET_SIZE);
+    bucket.push_back(token_id);
+    set_entries(e, index, position / BUCKET_SIZE, &bucket);
+
+    let mut token_slots = slots(e, index, token_id);
+    token_slots.set(token_id, position);
+    set_slots(e, index, token_id, &token_slots);
+
+    e.storage()
+        .persistent()
+        .set(&DataKey::IndexLen(index.clone()), &(position + 1));
+}
+
+/// Remove `token_id` from `index` (no-op if it isn't there).
+pub fn remove(e: &Env, index: &TicketIndex, token_id: u32) {
+    let mut token_slots = slots(e, index, token_id);
+    let position = match token_slots.get(token_id) {
+        Some(position) => position,
+        None => return,
+    };
+    token_slots.remove(token_id);
+    set_slots(e, index, token_id, &token_slots);
+
+    // Pop the last entry, then move it into the freed position
+    let last = len(e, index) - 1;
+    let mut last_bucket = entries(e, index, last / BUCKET_SIZE);
+    let moved = last_bucket.pop_back().unwrap();
+    set_entries(e, index, last / BUCKET_SIZE, &last_bucket);
+
+    if position != last {
+        let mut bucket = entries(e, index, position / BUCKET_SIZE);
+        bucket.set(position % BUCKET_SIZE, moved);
+        set_entries(e, index, position / BUCKET_SIZE, &bucket);
+
+        let mut moved_slots = slots(e, index, moved);
+        moved_slots.set(moved, position);
+        set_slots(e, index, moved, &moved_slots);
+    }
+
+    e.storage()
+        .persistent()
+        .set(&DataKey::IndexLen(index.clone()), &last);
+}
+
+/// Up to `limit` token IDs of `index` starting at position `start`.
+pub fn page(e: &Env, index: &TicketIndex, start: u32, limit: u32) -> Vec<u32> {
+    let end = len(e, index).min(start.saturating_add(limit));
+    let mut ids = Vec::new(e);
+    let mut position = start;
+    while position < end {
+        let bucket = entries(e, index, position / BUCKET_SIZE);
+        let bucket_end = end.min((position / BUCKET_SIZE + 1) * BUCKET_SIZE);
+        while position < bucket_end {
+            ids.push_back(bucket.get_unchecked(position % BUCKET_SIZE));
+            position += 1;
+        }
+    }
+    ids
+}
@@ -0,0 +1,61 @@
+/// events.rs — Contract events
+///
+/// Each event keeps a short fixed topic so indexers can filter on it; the
+/// subject (tier or token) follows as a second topic where there is one, and
+/// the data is published as a vec in field order.
+use soroban_sdk::{contractevent, Address, String, Symbol};
+
+/// The organizer moved the event to `new_start`.
+#[contractevent(topics = ["resched"], data_format = "vec")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rescheduled {
+    pub old_start: u64,
+    pub new_start: u64,
+    pub new_cutoff: u64,
+}
+
+/// The organizer cancelled the event; every ticket is refundable in full.
+#[contractevent(topics = ["cancelled"], data_format = "single-value")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cancelled {
+    pub reason: String,
+}
+
+/// A listed ticket changed hands on the resale market.
+#[contractevent(topics = ["resale"], data_format = "vec")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resold {
+    #[topic]
+    pub token_id: u32,
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: i128,
+}
+
+/// A released seat was offered to `holder`, the head of the waitlist.
+#[contractevent(topics = ["wl_offer"], data_format = "vec")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WaitlistOffer {
+    #[topic]
+    pub tier_symbol: Symbol,
+    pub holder: Address,
+    pub offer_expires_at: u64,
+}
+
+/// Every winner of a tier's lottery has been drawn.
+#[contractevent(topics = ["lottery"], data_format = "vec")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LotterySettled {
+    #[topic]
+    pub tier_symbol: Symbol,
+    pub entrants: u32,
+    pub winners: u32,
+}
+
+/// An oracle sample moved too far from the previous one and froze pricing.
+#[contractevent(topics = ["breaker"], data_format = "vec")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BreakerTripped {
+    pub previous: i128,
+    pub sample: i128,
+}
@@ -3,29 +3,94 @@
 #[cfg(test)]
 mod test;
 
-use soroban_sdk::{contract, contractimpl, token, Address, Env, String, Symbol};
+use soroban_sdk::{
+    contract, contractimpl, token, vec, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, String,
+    Symbol, Vec,
+};
 use stellar_access::ownable::{self as ownable, Ownable};
-use stellar_tokens::non_fungible::{Base, NonFungibleToken};
+use stellar_tokens::non_fungible::{burnable::emit_burn, emit_transfer, Base, NonFungibleToken};
 
 mod storage_types;
-use storage_types::{DataKey, EventInfo, PricingConfig, PricingStrategy, Ticket, Tier};
+use storage_types::{
+    AcceptedToken, BondingCurve, Cancellation, CheckInRecord, DataKey, Discount, DutchAuction,
+    EntryPolicy, EscrowTotals, EventInfo, Experiment, ExperimentVariant, Listing, Lottery,
+    OracleAdapter, OracleHealth, OracleObservation, PlatformFee, Presale, PriceBounds, PriceHealth,
+    PriceQuote, PriceSource, PricingConfig, PricingStrategy, PromoCode, RefundStep, ResaleConfig,
+    RevenueSplit, Ticket, TicketIndex, Tier, TransferPolicy, VariantSale, WaitlistConfig,
+    WaitlistNode, WaitlistQueue, WaitlistSpot,
+};
+
+mod enumeration;
+mod events;
+use events::{BreakerTripped, Cancelled, LotterySettled, Rescheduled, Resold, WaitlistOffer};
+
+mod merkle;
+mod metadata;
+use metadata::TicketMetadata;
 
 mod oracle;
-use oracle::{fetch_price_with_fallback, oracle_price_to_multiplier, DEFAULT_STALENESS_SECONDS};
+use oracle::{
+    fetch_median_price, fetch_price_with_fallback, oracle_price_to_multiplier, PriceFeed,
+    DEFAULT_STALENESS_SECONDS,
+};
 
 // Dynamic pricing constants
 const PRICE_INCREASE_BPS: i128 = 500; // 5% increase per tier threshold
 const EARLY_BIRD_DISCOUNT_BPS: i128 = 1000; // 10% discount max
 const ORACLE_PRECISION: i128 = 10000; // Assuming oracle returns multiplier in bps (e.g. 10000 = 1x)
+const CURVE_PRECISION: i128 = 1_000_000_000; // 9-decimal fixed point for curve growth
+const MAX_ORACLE_OBSERVATIONS: u32 = 8; // Samples kept for the time-weighted average
+const MAX_ORACLE_SOURCES: u32 = 8; // Sources queried for the median price
+
+// Fiat-priced tiers are denominated in cents
+const FIAT_DECIMALS: u32 = 2;
+
+// Oracle series sampled through `sample_feed`
+#[derive(Clone, PartialEq)]
+enum OracleFeed {
+    Multiplier,         // `oracle_pair` against `oracle_reference_price`
+    TokenPrice(String), // A payment token's price, by oracle pair
+}
+
+// Largest page returned by the enumeration views
+const MAX_PAGE_SIZE: u32 = 50;
+
+// Per-ticket metadata served by `token_uri`
+const MAX_TIER_NAME_LEN: u32 = 64;
+const MAX_IMAGE_URI_LEN: u32 = 256;
+const MAX_SEAT_LEN: u32 = 32;
+
+// Pricing experiments
+const MAX_EXPERIMENT_VARIANTS: u32 = 8;
+
+// Price quotes
+const QUOTE_TTL_SECONDS: u64 = 300; // Quotes hold their price for 5 minutes
+const QUOTE_TTL_LEDGERS: u32 = 120; // Keep the temporary entry alive past the quote expiry
+
+// Upper bound on tickets per `purchase_many` call, keeps the mint loop cheap
+const MAX_PURCHASE_QUANTITY: u32 = 20;
+
+// Gate presentations (signed QR proofs)
+const PRESENTATION_DOMAIN: &[u8] = b"GATHERA_TICKET_PRESENTATION";
+const MAX_PRESENTATION_WINDOW_SECONDS: u64 = 300; // Challenges live at most 5 minutes
+const PRESENTATION_NONCE_TTL_LEDGERS: u32 = 120; // Outlives the longest challenge window
+
+// Refund window reopened for holders when the event is rescheduled
+const RESCHEDULE_REFUND_GRACE_SECONDS: u64 = 7 * 86_400;
+
+// Waitlist offers stay exclusive for an hour unless configured otherwise
+const DEFAULT_WAITLIST_OFFER_SECONDS: u64 = 3_600;
 
 #[contract]
 pub struct SoulboundTicketContract;
 
 #[contractimpl]
 impl SoulboundTicketContract {
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         e: &Env,
         admin: Address,
+        platform: Address,
         name: String,
         symbol: String,
         uri: String,
@@ -35,21 +100,26 @@ impl SoulboundTicketContract {
         if e.storage().instance().has(&DataKey::Admin) {
             panic!("already initialized");
         }
+        // Refunds must close before proceeds can be withdrawn at the start
+        if refund_cutoff_time > start_time {
+            panic!("Refund cutoff must not be after the start");
+        }
 
         // Init Event Info
         let event_info = EventInfo {
             start_time,
             refund_cutoff_time,
+            refund_grace_until: 0,
+            refund_schedule: Vec::new(e),
         };
         e.storage().instance().set(&DataKey::EventInfo, &event_info);
         e.storage().instance().set(&DataKey::Admin, &admin);
-
-        // Init Token Counter
-        e.storage().instance().set(&DataKey::TokenIdCounter, &0u32);
+        e.storage().instance().set(&DataKey::Platform, &platform);
 
         // Init default PricingConfig (placeholder addresses, standard bounds)
         let default_config = PricingConfig {
             oracle_address: admin.clone(), // Update via set_pricing_config after deployment
+            oracle_adapter: OracleAdapter::Dia,
             dex_pool_address: admin.clone(), // Update via set_pricing_config after deployment
             price_floor: 0,
             price_ceiling: i128::MAX,
@@ -59,6 +129,11 @@ impl SoulboundTicketContract {
             oracle_pair: String::from_str(e, "XLM/USD"),
             oracle_reference_price: oracle::DIA_ORACLE_DECIMALS, // $1.00 baseline (1.0 * 10^8)
             max_oracle_age_seconds: DEFAULT_STALENESS_SECONDS,
+            oracle_sources: Vec::new(e),
+            min_quorum: 0,
+            max_deviation_bps: 0,
+            max_multiplier_move_bps: 0,
+            twap_window_seconds: 8 * 3600,
         };
         e.storage()
             .instance()
@@ -73,6 +148,27 @@ impl SoulboundTicketContract {
     pub fn set_pricing_config(e: &Env, config: PricingConfig) {
         let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
+
+        if config.oracle_sources.len() > MAX_ORACLE_SOURCES
+            || config.min_quorum > config.oracle_sources.len()
+        {
+            panic!("Invalid oracle sources");
+        }
+        if config.twap_window_seconds == 0 {
+            panic!("Invalid TWAP window");
+        }
+
+        // Samples from a different feed or baseline don't average with new ones
+        let current: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        if current.oracle_address != config.oracle_address
+            || current.oracle_adapter != config.oracle_adapter
+            || current.dex_pool_address != config.dex_pool_address
+            || current.oracle_pair != config.oracle_pair
+            || current.oracle_reference_price != config.oracle_reference_price
+            || current.oracle_sources != config.oracle_sources
+        {
+            Self::clear_observations(e);
+        }
         e.storage().instance().set(&DataKey::PricingConfig, &config);
     }
 
@@ -86,9 +182,12 @@ impl SoulboundTicketContract {
             e.storage().instance().get(&DataKey::PricingConfig).unwrap();
         config.oracle_reference_price = new_reference_price;
         e.storage().instance().set(&DataKey::PricingConfig, &config);
+        e.storage().instance().remove(&DataKey::OracleObservations);
     }
 
-    // Emergency freeze toggle
+    // Emergency freeze toggle.  Unfreezing (e.g. after the circuit breaker
+    // tripped) restarts the time-weighted average from the next oracle
+    // sample, so the breaker measures moves from the accepted level.
     pub fn emergency_freeze(e: &Env, freeze: bool) {
         let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
@@ -96,6 +195,9 @@ impl SoulboundTicketContract {
             e.storage().instance().get(&DataKey::PricingConfig).unwrap();
         config.is_frozen = freeze;
         e.storage().instance().set(&DataKey::PricingConfig, &config);
+        if !freeze {
+            Self::clear_observations(e);
+        }
     }
 
     // Add a new ticket tier
@@ -114,6 +216,9 @@ impl SoulboundTicketContract {
         if e.storage().persistent().has(&key) {
             panic!("Tier already exists");
         }
+        if name.len() > MAX_TIER_NAME_LEN {
+            panic!("Tier name too long");
+        }
 
         let tier = Tier {
             name,
@@ -123,50 +228,600 @@ impl SoulboundTicketContract {
             minted: 0,
             active: true,
             strategy,
+            max_per_wallet: 0,
+            cap_batch_mint: false,
+            entry_policy: EntryPolicy::SingleEntry,
+            checked_in: 0,
+            transfer_policy: TransferPolicy::Soulbound,
+            sale_start: 0,
+            sale_end: 0,
+            reserved: 0,
+            currency: None,
         };
 
         e.storage().persistent().set(&key, &tier);
+
+        let mut tiers: Vec<Symbol> = e
+            .storage()
+            .instance()
+            .get(&DataKey::TierList)
+            .unwrap_or(Vec::new(e));
+        tiers.push_back(tier_symbol);
+        e.storage().instance().set(&DataKey::TierList, &tiers);
+    }
+
+    /// Admin-only: change a tier's name, base price and supply.  Supply can
+    /// not drop below what has already been minted.
+    pub fn update_tier(
+        e: &Env,
+        tier_symbol: Symbol,
+        name: String,
+        base_price: i128,
+        max_supply: u32,
+    ) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        if max_supply < Self::seats_taken(e, &tier_symbol, &tier) {
+            panic!("Max supply below minted");
+        }
+        if name.len() > MAX_TIER_NAME_LEN {
+            panic!("Tier name too long");
+        }
+
+        tier.name = name;
+        tier.base_price = base_price;
+        tier.max_supply = max_supply;
+        if tier.minted == 0 {
+            // Nothing sold yet: the frozen price follows the new base price
+            tier.current_price = base_price;
+        }
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Admin-only: open or close a tier for sales.
+    pub fn set_tier_active(e: &Env, tier_symbol: Symbol, active: bool) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Tier(tier_symbol);
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.active = active;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Admin-only: restrict sales of a tier to `[sale_start, sale_end)`.
+    /// A bound of `0` leaves that side of the window open.
+    pub fn set_sale_window(e: &Env, tier_symbol: Symbol, sale_start: u64, sale_end: u64) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if sale_end != 0 && sale_end <= sale_start {
+            panic!("Sale end must be after sale start");
+        }
+
+        let key = DataKey::Tier(tier_symbol);
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.sale_start = sale_start;
+        tier.sale_end = sale_end;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Admin-only: price `tier_symbol` in a fiat `currency` (e.g. "USD"), in
+    /// cents: base price, tier price bounds and promo amounts are then all in
+    /// cents (the global `price_floor` / `price_ceiling` no longer apply), and
+    /// buyers pay the equivalent in an accepted payment token at purchase
+    /// time (see `set_accepted_token`).  `None` prices it in payment token
+    /// units again.  Only possible before the tier has sold anything.
+    pub fn set_tier_currency(e: &Env, tier_symbol: Symbol, currency: Option<Symbol>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        if tier.minted > 0 {
+            panic!("Tier already has sales");
+        }
+        if currency.is_some() {
+            if let Some(auction) = Self::get_dutch_auction(e, tier_symbol) {
+                if auction.uniform_clearing {
+                    panic!("Uniform clearing needs a token-priced tier");
+                }
+            }
+        }
+        tier.currency = currency;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Admin-only: accept `token` as payment for fiat-priced tiers.
+    pub fn set_accepted_token(e: &Env, token: Address, accepted: AcceptedToken) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        e.storage()
+            .persistent()
+            .set(&DataKey::AcceptedToken(token), &accepted);
+    }
+
+    pub fn remove_accepted_token(e: &Env, token: Address) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        e.storage()
+            .persistent()
+            .remove(&DataKey::AcceptedToken(token));
+    }
+
+    pub fn get_accepted_token(e: &Env, token: Address) -> Option<AcceptedToken> {
+        e.storage().persistent().get(&DataKey::AcceptedToken(token))
+    }
+
+    /// What the next ticket of `tier_symbol` costs in `payment_token` units
+    /// right now (the tier price itself for non-fiat tiers).
+    pub fn quote_in_token(e: &Env, tier_symbol: Symbol, payment_token: Address) -> i128 {
+        let price = Self::get_ticket_price(e, tier_symbol.clone());
+        Self::to_token_amounts(e, &tier_symbol, &payment_token, &vec![e, price], false)
+            .get_unchecked(0)
+    }
+
+    // What buyers pay for tickets of `tier_symbol` at `prices`, in
+    // `payment_token` units.  Fiat prices (cents of the tier's currency) are
+    // converted at the guarded oracle price of the token (see `sample_feed`;
+    // `record` as there), rounding up so the organizer is never
+    // short-changed.
+    fn to_token_amounts(
+        e: &Env,
+        tier_symbol: &Symbol,
+        payment_token: &Address,
+        prices: &Vec<i128>,
+        record: bool,
+    ) -> Vec<i128> {
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(tier_symbol.clone()))
+            .unwrap_or_else(|| panic!("Tier not found"));
+        let currency = match tier.currency {
+            Some(currency) => currency,
+            None => return prices.clone(),
+        };
+        let accepted = Self::get_accepted_token(e, payment_token.clone())
+            .unwrap_or_else(|| panic!("Payment token not accepted"));
+        if accepted.currency != currency {
+            panic!("Payment token currency mismatch");
+        }
+
+        // Token price in `currency`, 8 decimals
+        let token_price = match accepted.pair {
+            Some(pair) => {
+                let mut config: PricingConfig =
+                    e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+                let (average, _) =
+                    Self::sample_feed(e, &mut config, &OracleFeed::TokenPrice(pair), record);
+                average
+                    .filter(|price| *price > 0)
+                    .unwrap_or_else(|| panic!("Payment token price unavailable"))
+            }
+            None => oracle::DIA_ORACLE_DECIMALS,
+        };
+
+        // amount = cents * 10^token_decimals * 10^8 / (10^2 * token_price)
+        let token_decimals = token::Client::new(e, payment_token).decimals();
+        let numerator_scale = 10i128
+            .checked_pow(token_decimals)
+            .and_then(|scale| scale.checked_mul(oracle::DIA_ORACLE_DECIMALS))
+            .unwrap_or_else(|| panic!("Price overflow"));
+        let denominator = 10i128.pow(FIAT_DECIMALS) * token_price;
+
+        let mut amounts = Vec::new(e);
+        for price in prices.iter() {
+            let numerator = price
+                .checked_mul(numerator_scale)
+                .unwrap_or_else(|| panic!("Price overflow"));
+            amounts.push_back((numerator + denominator - 1) / denominator);
+        }
+        amounts
+    }
+
+    /// Every tier in creation order with its current state.
+    pub fn list_tiers(e: &Env) -> Vec<(Symbol, Tier)> {
+        let tiers: Vec<Symbol> = e
+            .storage()
+            .instance()
+            .get(&DataKey::TierList)
+            .unwrap_or(Vec::new(e));
+
+        let mut result = Vec::new(e);
+        for tier_symbol in tiers.iter() {
+            let tier: Tier = e
+                .storage()
+                .persistent()
+                .get(&DataKey::Tier(tier_symbol.clone()))
+                .unwrap();
+            result.push_back((tier_symbol, tier));
+        }
+        result
     }
 
-    /// Fetch the current external price multiplier using the real DIA oracle.
+    /// Fetch the current price of `pair`.
     ///
-    /// Strategy:
+    /// With `oracle_sources` configured this is the median of their fresh
+    /// answers (see `oracle::fetch_median_price`).  Otherwise:
     ///  1. Call `DiaOraclePriceClient::try_get_value(pair)` on the configured oracle.
     ///  2. Verify that the returned timestamp is within `max_oracle_age_seconds`.
     ///  3. If the oracle is stale or the cross-contract call fails, fall back to
     ///     `DexPriceRouterClient::try_get_spot_price(pair)` on the DEX address.
-    ///  4. If both fail, return `ORACLE_PRECISION` (neutral — no adjustment).
+    ///  4. If both fail, the feed is `Unavailable` (no new sample).
     ///
-    /// The raw price (8 decimals, $1.00 == 100_000_000) is converted into a
-    /// `ORACLE_PRECISION`-scaled multiplier using the stored `oracle_reference_price`.
-    fn fetch_oracle_multiplier(e: &Env, config: &PricingConfig) -> i128 {
-        match fetch_price_with_fallback(
-            e,
-            &config.oracle_address,
-            &config.dex_pool_address,
-            config.oracle_pair.clone(),
-            config.max_oracle_age_seconds,
-        ) {
-            Some(result) => oracle_price_to_multiplier(
-                result.price,
-                config.oracle_reference_price,
-                ORACLE_PRECISION,
-            ),
-            // Both oracle and DEX unavailable: apply neutral multiplier (no adjustment)
-            None => ORACLE_PRECISION,
+    /// Prices are 8 decimals ($1.00 == 100_000_000); `oracle_multiplier` turns
+    /// them into multipliers.
+    fn fetch_oracle_price(e: &Env, config: &PricingConfig, pair: &String) -> PriceFeed {
+        if config.oracle_sources.is_empty() {
+            match fetch_price_with_fallback(
+                e,
+                &config.oracle_address,
+                &config.oracle_adapter,
+                &config.dex_pool_address,
+                pair.clone(),
+                config.max_oracle_age_seconds,
+            ) {
+                Some(result) => PriceFeed::Price(result),
+                None => PriceFeed::Unavailable,
+            }
+        } else {
+            fetch_median_price(
+                e,
+                &config.oracle_sources,
+                pair.clone(),
+                config.max_oracle_age_seconds,
+                config.min_quorum,
+                config.max_deviation_bps,
+            )
+        }
+    }
+
+    /// Time-weighted average of the recent oracle multipliers (see
+    /// `sample_feed`).  Until the first successful sample, or once every
+    /// sample has expired, the multiplier is neutral.  `None` when the
+    /// sources can't be trusted right now or the circuit breaker just
+    /// tripped: prices hold at `current_price`.
+    fn oracle_multiplier(e: &Env, config: &mut PricingConfig, record: bool) -> Option<i128> {
+        match Self::sample_feed(e, config, &OracleFeed::Multiplier, record) {
+            (_, true) => None,
+            (average, false) => Some(average.unwrap_or(ORACLE_PRECISION)),
+        }
+    }
+
+    /// Sample `feed` if `update_frequency` has elapsed since its last sample
+    /// (and pricing is not frozen), then average its samples.  While frozen
+    /// the last accepted sample is returned as is, so fiat tiers keep
+    /// converting at the rate pricing froze at however long the freeze lasts.
+    ///
+    /// Each sample is weighted by how long it was the latest one within the
+    /// last `twap_window_seconds`, so a move only shows in proportion to how
+    /// long it has been observed.  Samples that fell out of the window are
+    /// dropped and at most `MAX_ORACLE_OBSERVATIONS` are kept.  A sample that
+    /// moves more than `max_multiplier_move_bps` from the previous one trips
+    /// the circuit breaker: pricing freezes until the admin unfreezes it and
+    /// the spike is left out of the average.
+    ///
+    /// Returns the average (None without samples) and whether the feed must
+    /// be held, because the sources can't be trusted right now or the breaker
+    /// just tripped.  Only with `record` are the new sample, the health
+    /// report and a tripped breaker persisted; views pass `false` and see the
+    /// same average without writing anything.
+    fn sample_feed(
+        e: &Env,
+        config: &mut PricingConfig,
+        feed: &OracleFeed,
+        record: bool,
+    ) -> (Option<i128>, bool) {
+        let now = e.ledger().timestamp();
+        let window_start = now.saturating_sub(config.twap_window_seconds);
+        let mut observations = Self::feed_observations(e, feed);
+
+        let last_sample = match feed {
+            OracleFeed::Multiplier => config.last_update_time,
+            OracleFeed::TokenPrice(_) => observations.last().map_or(0, |last| last.timestamp),
+        };
+        let due = now >= last_sample.saturating_add(config.update_frequency);
+        // A token feed without any sample has no rate to hold, so it takes
+        // its first one even while pricing is frozen
+        let frozen = config.is_frozen;
+        let unseeded = observations.is_empty() && *feed != OracleFeed::Multiplier;
+        let mut changed = false;
+        let mut held = false;
+        if (!frozen && (observations.is_empty() || due)) || unseeded {
+            let pair = match feed {
+                OracleFeed::Multiplier => config.oracle_pair.clone(),
+                OracleFeed::TokenPrice(pair) => pair.clone(),
+            };
+            match Self::fetch_oracle_price(e, config, &pair) {
+                PriceFeed::Price(result) => {
+                    let value = match feed {
+                        OracleFeed::Multiplier => oracle_price_to_multiplier(
+                            result.price,
+                            config.oracle_reference_price,
+                            ORACLE_PRECISION,
+                        ),
+                        OracleFeed::TokenPrice(_) => result.price,
+                    };
+                    if record && *feed == OracleFeed::Multiplier {
+                        let source = if !config.oracle_sources.is_empty() {
+                            PriceSource::Median
+                        } else if result.from_primary {
+                            PriceSource::Primary
+                        } else {
+                            PriceSource::DexFallback
+                        };
+                        e.storage().instance().set(
+                            &DataKey::OracleHealth,
+                            &OracleHealth {
+                                source,
+                                price_timestamp: result.timestamp,
+                                multiplier: value,
+                            },
+                        );
+                    }
+
+                    // Circuit breaker: a jump from the previous sample freezes
+                    // pricing until the admin unfreezes it
+                    let tripped = match observations.last() {
+                        Some(previous) if config.max_multiplier_move_bps > 0 => {
+                            (value - previous.multiplier).abs() * 10000
+                                > previous.multiplier * config.max_multiplier_move_bps as i128
+                        }
+                        _ => false,
+                    };
+                    if *feed == OracleFeed::Multiplier {
+                        config.last_update_time = now;
+                    }
+                    if tripped {
+                        // The spike is left out of the average
+                        if record {
+                            config.is_frozen = true;
+                            e.storage().instance().set(&DataKey::PricingConfig, config);
+                            BreakerTripped {
+                                previous: observations.last().unwrap().multiplier,
+                                sample: value,
+                            }
+                            .publish(e);
+                        }
+                        held = true;
+                    } else {
+                        observations.push_back(OracleObservation {
+                            multiplier: value,
+                            timestamp: now,
+                        });
+                        if observations.len() > MAX_ORACLE_OBSERVATIONS {
+                            observations.pop_front();
+                        }
+                        changed = true;
+                    }
+                }
+                PriceFeed::Unavailable => {}
+                PriceFeed::Untrusted => {
+                    if record && *feed == OracleFeed::Multiplier {
+                        let mut health = Self::oracle_health(e);
+                        health.source = PriceSource::Untrusted;
+                        e.storage().instance().set(&DataKey::OracleHealth, &health);
+                    }
+                    held = true;
+                }
+            }
+        }
+
+        // Drop samples that were superseded, or taken, before the window
+        // opened.  Nothing new is sampled while frozen, so nothing expires
+        // either and the last accepted sample stands in for the average.
+        if !frozen {
+            while let Some(first) = observations.first() {
+                let expired = match observations.get(1) {
+                    Some(next) => next.timestamp <= window_start,
+                    None => first.timestamp < window_start,
+                };
+                if !expired {
+                    break;
+                }
+                observations.pop_front();
+                changed = true;
+            }
+        }
+        if record && changed {
+            Self::store_observations(e, config, feed, &observations);
+        }
+
+        let latest = match observations.last() {
+            Some(observation) => observation,
+            None => return (None, held),
+        };
+        if frozen {
+            return (Some(latest.multiplier), held);
+        }
+        let first = observations.first_unchecked();
+        let start = first.timestamp.max(window_start);
+        let span = now.saturating_sub(start);
+        if span == 0 {
+            return (Some(latest.multiplier), held);
+        }
+        let mut weighted: i128 = 0;
+        for i in 0..observations.len() {
+            let observation = observations.get_unchecked(i);
+            let from = observation.timestamp.max(window_start);
+            let until = match observations.get(i + 1) {
+                Some(next) => next.timestamp,
+                None => now,
+            };
+            weighted += observation.multiplier * until.saturating_sub(from) as i128;
+        }
+        (Some(weighted / span as i128), held)
+    }
+
+    fn feed_observations(e: &Env, feed: &OracleFeed) -> Vec<OracleObservation> {
+        match feed {
+            OracleFeed::Multiplier => Self::oracle_observations(e),
+            OracleFeed::TokenPrice(pair) => Self::token_price_observations(e)
+                .get(pair.clone())
+                .unwrap_or_else(|| Vec::new(e)),
+        }
+    }
+
+    fn token_price_observations(e: &Env) -> Map<String, Vec<OracleObservation>> {
+        e.storage()
+            .instance()
+            .get(&DataKey::TokenPriceObservations)
+            .unwrap_or_else(|| Map::new(e))
+    }
+
+    fn store_observations(
+        e: &Env,
+        config: &PricingConfig,
+        feed: &OracleFeed,
+        observations: &Vec<OracleObservation>,
+    ) {
+        match feed {
+            OracleFeed::Multiplier => {
+                e.storage()
+                    .instance()
+                    .set(&DataKey::OracleObservations, observations);
+                e.storage().instance().set(&DataKey::PricingConfig, config);
+            }
+            OracleFeed::TokenPrice(pair) => {
+                let mut all = Self::token_price_observations(e);
+                all.set(pair.clone(), observations.clone());
+                e.storage()
+                    .instance()
+                    .set(&DataKey::TokenPriceObservations, &all);
+            }
+        }
+    }
+
+    // Forget every sample, e.g. when the sources change
+    fn clear_observations(e: &Env) {
+        e.storage().instance().remove(&DataKey::OracleObservations);
+        e.storage()
+            .instance()
+            .remove(&DataKey::TokenPriceObservations);
+    }
+
+    /// Permissionless: sample the oracle if it is due (see `update_frequency`)
+    /// and return the time-weighted multiplier (None while the sources can't
+    /// be trusted).  Keepers can call this on a schedule so the average
+    /// doesn't depend on purchase traffic.
+    pub fn refresh_oracle_price(e: &Env) -> Option<i128> {
+        let mut config: PricingConfig =
+            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        Self::oracle_multiplier(e, &mut config, true)
+    }
+
+    /// Where the latest oracle sample came from, how old the price behind it
+    /// is and the multiplier it produced.
+    pub fn price_health(e: &Env) -> PriceHealth {
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        let health = Self::oracle_health(e);
+        PriceHealth {
+            source: health.source,
+            age_seconds: e
+                .ledger()
+                .timestamp()
+                .saturating_sub(health.price_timestamp),
+            last_multiplier: health.multiplier,
+            is_frozen: config.is_frozen,
         }
     }
 
+    fn oracle_health(e: &Env) -> OracleHealth {
+        e.storage()
+            .instance()
+            .get(&DataKey::OracleHealth)
+            .unwrap_or(OracleHealth {
+                source: PriceSource::Neutral,
+                price_timestamp: e.ledger().timestamp(),
+                multiplier: ORACLE_PRECISION,
+            })
+    }
+
+    /// Oracle samples currently in the time-weighted average, oldest first.
+    pub fn oracle_observations(e: &Env) -> Vec<OracleObservation> {
+        e.storage()
+            .instance()
+            .get(&DataKey::OracleObservations)
+            .unwrap_or_else(|| Vec::new(e))
+    }
+
     // Dynamic pricing query
     pub fn get_ticket_price(e: &Env, tier_symbol: Symbol) -> i128 {
-        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
-        let key = DataKey::Tier(tier_symbol);
-        let tier: Tier = e.storage().persistent().get(&key).unwrap();
+        Self::price_for(e, &tier_symbol, None, false)
+    }
+
+    /// Price of the next ticket of `tier_symbol` for `buyer`, i.e. in the
+    /// experiment variant `buyer` is bucketed into (see `set_experiment`).
+    pub fn get_ticket_price_for(e: &Env, buyer: Address, tier_symbol: Symbol) -> i128 {
+        Self::price_for(e, &tier_symbol, Some(&buyer), false)
+    }
+
+    // With `record`, a due oracle sample is stored (see `oracle_multiplier`);
+    // only purchase paths record, so price views stay read-only.
+    fn price_for(e: &Env, tier_symbol: &Symbol, buyer: Option<&Address>, record: bool) -> i128 {
+        let mut config: PricingConfig =
+            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e.storage().persistent().get(&key).unwrap();
 
         if config.is_frozen {
             return tier.current_price;
         }
+        if let Some(buyer) = buyer {
+            Self::apply_experiment(e, tier_symbol, &mut tier, buyer);
+        }
+
+        // Apply external Oracle factors using the real DIA oracle integration
+        let oracle_multiplier = match Self::oracle_multiplier(e, &mut config, record) {
+            Some(multiplier) => multiplier,
+            // Price sources disagree: hold the current price as if frozen
+            None => return tier.current_price,
+        };
+
+        // We only return the price here. It is updated during `purchase`.
+        Self::price_with_multiplier(e, &config, tier_symbol, &tier, oracle_multiplier)
+    }
+
+    /// Total cost of buying `qty` tickets of `tier_symbol` in a single call,
+    /// walking the demand (or bonding) curve one ticket at a time.  This is
+    /// exactly what `purchase_many` charges for the same quantity (in cents
+    /// for fiat-priced tiers, see `quote_in_token`).
+    pub fn quote_total_price(e: &Env, tier_symbol: Symbol, qty: u32) -> i128 {
+        let mut total: i128 = 0;
+        for price in Self::unit_prices(e, &tier_symbol, None, qty, false).iter() {
+            total += price;
+        }
+        total
+    }
 
+    // Price of the next ticket of `tier` for an already fetched oracle multiplier
+    fn price_with_multiplier(
+        e: &Env,
+        config: &PricingConfig,
+        tier_symbol: &Symbol,
+        tier: &Tier,
+        oracle_multiplier: i128,
+    ) -> i128 {
         // Base price
         let mut price = tier.base_price;
 
@@ -200,159 +855,2379 @@ impl SoulboundTicketContract {
                 // Floor starts higher (+20%)
                 price += price * 2000 / 10000;
             }
+            PricingStrategy::DutchAuction => {
+                let auction = Self::get_dutch_auction(e, tier_symbol.clone())
+                    .unwrap_or_else(|| panic!("Auction not configured"));
+                price = Self::auction_price(&auction, e.ledger().timestamp());
+            }
+            PricingStrategy::BondingCurve => {
+                let curve = Self::get_bonding_curve(e, tier_symbol.clone())
+                    .unwrap_or_else(|| panic!("Curve not configured"));
+                price = Self::curve_price(price, &curve, tier.minted);
+            }
         }
 
-        // Apply external Oracle factors using the real DIA oracle integration
-        let oracle_multiplier = Self::fetch_oracle_multiplier(e, &config);
-        price = price * oracle_multiplier / ORACLE_PRECISION;
+        // Fiat tiers are converted through the oracle at purchase instead
+        if tier.currency.is_none() {
+            price = price * oracle_multiplier / ORACLE_PRECISION;
+        }
 
         // Apply bounds
-        price = price.max(config.price_floor).min(config.price_ceiling);
+        let bounds = Self::price_bounds(e, config, tier_symbol, tier);
+        price.max(bounds.floor).min(bounds.ceiling)
+    }
 
-        // We only return the price here. It is updated during `purchase`.
-        price
+    // Bounds on `tier`'s price in its own unit: its own if set, otherwise the
+    // global token-unit bounds for token-priced tiers and none for fiat ones.
+    fn price_bounds(
+        e: &Env,
+        config: &PricingConfig,
+        tier_symbol: &Symbol,
+        tier: &Tier,
+    ) -> PriceBounds {
+        if let Some(bounds) = Self::get_tier_price_bounds(e, tier_symbol.clone()) {
+            return bounds;
+        }
+        match tier.currency {
+            Some(_) => PriceBounds {
+                floor: 0,
+                ceiling: i128::MAX,
+            },
+            None => PriceBounds {
+                floor: config.price_floor,
+                ceiling: config.price_ceiling,
+            },
+        }
     }
 
-    // Batch Minting for Organizer
-    pub fn batch_mint(e: &Env, to: Address, tier_symbol: Symbol, amount: u32) {
+    /// Admin-only: bound the price of `tier_symbol` to `[floor, ceiling]` in
+    /// the tier's own unit (cents for fiat-priced tiers), in place of the
+    /// global `price_floor` / `price_ceiling`.  `None` removes the bounds.
+    pub fn set_tier_price_bounds(e: &Env, tier_symbol: Symbol, bounds: Option<PriceBounds>) {
         let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
 
-        let key = DataKey::Tier(tier_symbol.clone());
-        let mut tier: Tier = e
+        if !e
             .storage()
             .persistent()
-            .get(&key)
-            .unwrap_or_else(|| panic!("Tier not found"));
-
-        if tier.minted + amount > tier.max_supply {
-            panic!("Exceeds tier max supply");
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
         }
-
-        for _ in 0..amount {
-            // custom sequential increment
-            let mut counter: u32 = e
-                .storage()
-                .instance()
-                .get(&DataKey::TokenIdCounter)
-                .unwrap();
-            counter += 1;
-            let token_id = counter;
-            e.storage()
-                .instance()
-                .set(&DataKey::TokenIdCounter, &counter);
-
-            Base::sequential_mint(e, &to);
-
-            let ticket = Ticket {
-                tier_symbol: tier_symbol.clone(),
-                purchase_time: e.ledger().timestamp(),
-                price_paid: 0, // Admin mints are free
-                is_valid: true,
-            };
-            e.storage()
-                .persistent()
-                .set(&DataKey::Ticket(token_id), &ticket);
+        let key = DataKey::TierPriceBounds(tier_symbol);
+        match bounds {
+            Some(bounds) => {
+                if bounds.floor < 0 || bounds.ceiling < bounds.floor {
+                    panic!("Invalid price bounds");
+                }
+                e.storage().persistent().set(&key, &bounds);
+            }
+            None => e.storage().persistent().remove(&key),
         }
-
-        tier.minted += amount;
-        e.storage().persistent().set(&key, &tier);
     }
 
-    // Purchase a ticket
-    pub fn purchase(e: &Env, buyer: Address, payment_token: Address, tier_symbol: Symbol) {
-        buyer.require_auth();
+    pub fn get_tier_price_bounds(e: &Env, tier_symbol: Symbol) -> Option<PriceBounds> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::TierPriceBounds(tier_symbol))
+    }
 
-        let key = DataKey::Tier(tier_symbol.clone());
+    // Prices of the next `qty` tickets of a tier (for `buyer`'s experiment
+    // variant, if any). The oracle is queried once and demand-based strategies
+    // see `minted` advance as each ticket is priced.
+    fn unit_prices(
+        e: &Env,
+        tier_symbol: &Symbol,
+        buyer: Option<&Address>,
+        qty: u32,
+        record: bool,
+    ) -> Vec<i128> {
+        let mut config: PricingConfig =
+            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
         let mut tier: Tier = e
             .storage()
             .persistent()
-            .get(&key)
+            .get(&DataKey::Tier(tier_symbol.clone()))
             .unwrap_or_else(|| panic!("Tier not found"));
 
-        if !tier.active {
-            panic!("Tier is not active");
+        let mut prices = Vec::new(e);
+        let oracle_multiplier = if config.is_frozen {
+            None
+        } else {
+            Self::oracle_multiplier(e, &mut config, record)
+        };
+        let oracle_multiplier = match oracle_multiplier {
+            Some(multiplier) => multiplier,
+            // Frozen, or the price sources disagree: hold the current price
+            None => {
+                for _ in 0..qty {
+                    prices.push_back(tier.current_price);
+                }
+                return prices;
+            }
+        };
+        if let Some(buyer) = buyer {
+            Self::apply_experiment(e, tier_symbol, &mut tier, buyer);
         }
-        if tier.minted >= tier.max_supply {
-            panic!("Tier sold out");
+        for _ in 0..qty {
+            prices.push_back(Self::price_with_multiplier(
+                e,
+                &config,
+                tier_symbol,
+                &tier,
+                oracle_multiplier,
+            ));
+            tier.minted += 1;
         }
+        prices
+    }
 
-        let price = Self::get_ticket_price(e, tier_symbol.clone());
+    /// Admin-only: sell `tier_symbol` by Dutch auction (switches the tier to
+    /// the `DutchAuction` strategy).  Cannot be changed once tickets sold.
+    pub fn set_dutch_auction(e: &Env, tier_symbol: Symbol, auction: DutchAuction) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if auction.reserve_price <= 0 || auction.start_price < auction.reserve_price {
+            panic!("Invalid auction prices");
+        }
+        if auction.end_time <= auction.start_time || auction.step_seconds == 0 {
+            panic!("Invalid auction window");
+        }
+        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        if auction.uniform_clearing && auction.end_time > event_info.start_time {
+            // Rebates are settled before the proceeds unlock at event start
+            panic!("Auction must end before the event starts");
+        }
+        if let Some(existing) = Self::get_dutch_auction(e, tier_symbol.clone()) {
+            if existing.clearing_price != 0 {
+                panic!("Auction already has sales");
+            }
+        }
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        if auction.uniform_clearing && tier.currency.is_some() {
+            // Uniform clearing is only offered on token-priced tiers
+            panic!("Uniform clearing needs a token-priced tier");
+        }
+        tier.strategy = PricingStrategy::DutchAuction;
+        e.storage().persistent().set(&key, &tier);
+
+        let auction = DutchAuction {
+            clearing_price: 0,
+            ..auction
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::DutchAuction(tier_symbol), &auction);
+    }
+
+    pub fn get_dutch_auction(e: &Env, tier_symbol: Symbol) -> Option<DutchAuction> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::DutchAuction(tier_symbol))
+    }
+
+    /// With uniform clearing, refund the difference between what `token_id`
+    /// was bought for and what it would have cost at the auction's clearing
+    /// price.  Only tickets sold at an auction step qualify (not waitlist,
+    /// lottery or other-variant sales).  Open from the end of
+    /// the auction (or sell-out) until the event starts; returns the rebate.
+    pub fn claim_auction_rebate(e: &Env, owner: Address, token_id: u32) -> i128 {
+        owner.require_auth();
+
+        if owner != Self::owner_of(e, token_id) {
+            panic!("Not the ticket owner");
+        }
+        let mut ticket: Ticket = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
+            .unwrap();
+        let auction = Self::get_dutch_auction(e, ticket.tier_symbol.clone())
+            .unwrap_or_else(|| panic!("Auction not configured"));
+        if !auction.uniform_clearing {
+            panic!("Auction has no uniform clearing");
+        }
+
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(ticket.tier_symbol.clone()))
+            .unwrap();
+        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        let now = e.ledger().timestamp();
+        if now < auction.end_time && tier.minted < tier.max_supply {
+            panic!("Auction still running");
+        }
+        if now >= event_info.start_time {
+            panic!("Rebate window closed");
+        }
+
+        // Scale what was paid by clearing price / step price, so the rebate is
+        // in the paid unit whatever oracle multiplier or discount applied
+        let step_key = DataKey::AuctionStep(token_id);
+        let step_price: i128 = e
+            .storage()
+            .persistent()
+            .get(&step_key)
+            .unwrap_or_else(|| panic!("Not sold at an auction step"));
+        let cleared = ticket.price_paid * auction.clearing_price / step_price;
+        let rebate = ticket.price_paid - cleared;
+        if !ticket.is_valid || rebate <= 0 {
+            panic!("No rebate due");
+        }
+        let payment_token = ticket.payment_token.clone().unwrap();
+        let token_client = token::Client::new(e, &payment_token);
+        token_client.transfer(&e.current_contract_address(), &owner, &rebate);
+
+        let mut totals = Self::get_escrow_totals(e, payment_token.clone());
+        totals.refunded += rebate;
+        e.storage()
+            .persistent()
+            .set(&DataKey::Escrow(payment_token), &totals);
+
+        // Later refunds are based on the clearing price
+        ticket.price_paid = cleared;
+        e.storage()
+            .persistent()
+            .set(&DataKey::Ticket(token_id), &ticket);
+        e.storage()
+            .persistent()
+            .set(&step_key, &auction.clearing_price);
+        rebate
+    }
+
+    /// Admin-only: run pricing experiment `id` on `tier_symbol`.  Each buyer
+    /// is bucketed into one of `variants` by hashing their address with the
+    /// experiment ID, so they always see the same variant; the variant's
+    /// strategy replaces the tier's for them.  Starting an experiment resets
+    /// its per-variant sales counters; an ID cannot be run twice on a tier,
+    /// so refunds of an earlier run never count against a later one.
+    pub fn set_experiment(e: &Env, tier_symbol: Symbol, id: u32, variants: Vec<ExperimentVariant>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if !e
+            .storage()
+            .persistent()
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
+        }
+        if variants.is_empty() || variants.len() > MAX_EXPERIMENT_VARIANTS {
+            panic!("Invalid variant count");
+        }
+        let mut reset = Vec::new(e);
+        let mut total_weight: u64 = 0;
+        for variant in variants.iter() {
+            total_weight += variant.weight as u64;
+            reset.push_back(ExperimentVariant {
+                sales: 0,
+                revenue: Map::new(e),
+                ..variant
+            });
+        }
+        if total_weight == 0 || total_weight > u32::MAX as u64 {
+            panic!("Invalid variant weights");
+        }
+        let used_key = DataKey::ExperimentUsed(tier_symbol.clone(), id);
+        if e.storage().persistent().has(&used_key) {
+            panic!("Experiment ID already used");
+        }
+        e.storage().persistent().set(&used_key, &true);
+
+        let experiment = Experiment {
+            id,
+            variants: reset,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::Experiment(tier_symbol), &experiment);
+    }
+
+    /// Admin-only: stop the experiment on `tier_symbol`; everyone is priced
+    /// by the tier's own strategy again.
+    pub fn end_experiment(e: &Env, tier_symbol: Symbol) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        e.storage()
+            .persistent()
+            .remove(&DataKey::Experiment(tier_symbol));
+    }
+
+    /// The running experiment of `tier_symbol`, with sales and revenue per
+    /// variant.  Revenue is kept per payment token.
+    pub fn get_experiment(e: &Env, tier_symbol: Symbol) -> Option<Experiment> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Experiment(tier_symbol))
+    }
+
+    /// Index of the variant `buyer` is bucketed into, if an experiment runs.
+    pub fn experiment_variant(e: &Env, tier_symbol: Symbol, buyer: Address) -> Option<u32> {
+        Self::get_experiment(e, tier_symbol).map(|experiment| Self::bucket(e, &experiment, &buyer))
+    }
+
+    // Weighted variant choice from sha256(buyer address XDR || experiment id).
+    // The first 64 bits of the hash are scaled onto the total weight with a
+    // multiply-shift, which unlike `%` gives every weight unit an equal share.
+    fn bucket(e: &Env, experiment: &Experiment, buyer: &Address) -> u32 {
+        let mut data = Bytes::new(e);
+        data.append(&buyer.clone().to_xdr(e));
+        data.extend_from_array(&experiment.id.to_be_bytes());
+        let hash = e.crypto().sha256(&data).to_array();
+
+        let mut total_weight: u32 = 0;
+        for variant in experiment.variants.iter() {
+            total_weight += variant.weight;
+        }
+        let mut wide = [0u8; 8];
+        wide.copy_from_slice(&hash[..8]);
+        let mut point = ((u64::from_be_bytes(wide) as u128 * total_weight as u128) >> 64) as u32;
+        for (index, variant) in experiment.variants.iter().enumerate() {
+            if point < variant.weight {
+                return index as u32;
+            }
+            point -= variant.weight;
+        }
+        unreachable!()
+    }
+
+    // Price `tier` with `buyer`'s variant strategy
+    fn apply_experiment(e: &Env, tier_symbol: &Symbol, tier: &mut Tier, buyer: &Address) {
+        if let Some(experiment) = Self::get_experiment(e, tier_symbol.clone()) {
+            let index = Self::bucket(e, &experiment, buyer);
+            tier.strategy = experiment.variants.get_unchecked(index).strategy;
+        }
+    }
+
+    // Count a purchase of `token_ids` towards `buyer`'s variant
+    fn record_conversion(
+        e: &Env,
+        tier_symbol: &Symbol,
+        buyer: &Address,
+        payment_token: &Address,
+        token_ids: &Vec<u32>,
+        revenue: i128,
+    ) {
+        if let Some(mut experiment) = Self::get_experiment(e, tier_symbol.clone()) {
+            let index = Self::bucket(e, &experiment, buyer);
+            let mut variant = experiment.variants.get_unchecked(index);
+            variant.sales += token_ids.len();
+            let earned = variant.revenue.get(payment_token.clone()).unwrap_or(0);
+            variant.revenue.set(payment_token.clone(), earned + revenue);
+            experiment.variants.set(index, variant);
+            e.storage()
+                .persistent()
+                .set(&DataKey::Experiment(tier_symbol.clone()), &experiment);
+
+            let sale = VariantSale {
+                experiment_id: experiment.id,
+                variant: index,
+            };
+            for token_id in token_ids.iter() {
+                e.storage()
+                    .persistent()
+                    .set(&DataKey::TicketVariant(token_id), &sale);
+            }
+        }
+    }
+
+    // Take a refunded ticket out of the variant it was sold in, if that
+    // experiment is still running
+    fn reverse_conversion(
+        e: &Env,
+        tier_symbol: &Symbol,
+        token_id: u32,
+        payment_token: &Address,
+        refunded: i128,
+    ) {
+        let sale_key = DataKey::TicketVariant(token_id);
+        let sale: Option<VariantSale> = e.storage().persistent().get(&sale_key);
+        let Some(sale) = sale else {
+            return;
+        };
+        e.storage().persistent().remove(&sale_key);
+        if let Some(mut experiment) = Self::get_experiment(e, tier_symbol.clone()) {
+            if experiment.id != sale.experiment_id {
+                return;
+            }
+            let Some(mut variant) = experiment.variants.get(sale.variant) else {
+                return;
+            };
+            variant.sales = variant.sales.saturating_sub(1);
+            let earned = variant.revenue.get(payment_token.clone()).unwrap_or(0);
+            variant
+                .revenue
+                .set(payment_token.clone(), (earned - refunded).max(0));
+            experiment.variants.set(sale.variant, variant);
+            e.storage()
+                .persistent()
+                .set(&DataKey::Experiment(tier_symbol.clone()), &experiment);
+        }
+    }
+
+    /// Admin-only: price `tier_symbol` along `curve` (switches the tier to the
+    /// `BondingCurve` strategy).
+    pub fn set_bonding_curve(e: &Env, tier_symbol: Symbol, curve: BondingCurve) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let valid = match curve {
+            BondingCurve::Linear(slope) => slope >= 0,
+            BondingCurve::Exponential(rate_bps) => rate_bps > 0,
+            BondingCurve::Step(step_size, increment) => step_size > 0 && increment >= 0,
+        };
+        if !valid {
+            panic!("Invalid curve parameters");
+        }
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.strategy = PricingStrategy::BondingCurve;
+        e.storage().persistent().set(&key, &tier);
+        e.storage()
+            .persistent()
+            .set(&DataKey::BondingCurve(tier_symbol), &curve);
+    }
+
+    pub fn get_bonding_curve(e: &Env, tier_symbol: Symbol) -> Option<BondingCurve> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::BondingCurve(tier_symbol))
+    }
+
+    // Price of the next ticket on `curve` after `minted` tickets.  All
+    // arithmetic is checked: a curve that outgrows i128 stops selling
+    // instead of wrapping around.
+    fn curve_price(base_price: i128, curve: &BondingCurve, minted: u32) -> i128 {
+        let price = match *curve {
+            BondingCurve::Linear(slope) => slope
+                .checked_mul(minted as i128)
+                .and_then(|growth| base_price.checked_add(growth)),
+            BondingCurve::Step(step_size, increment) => increment
+                .checked_mul((minted / step_size) as i128)
+                .and_then(|growth| base_price.checked_add(growth)),
+            BondingCurve::Exponential(rate_bps) => Self::curve_growth(rate_bps, minted)
+                .and_then(|factor| Self::mul_fixed(base_price, factor)),
+        };
+        price.unwrap_or_else(|| panic!("Price overflow"))
+    }
+
+    // (1 + rate_bps / 10000)^minted in fixed point, by square-and-multiply
+    fn curve_growth(rate_bps: u32, minted: u32) -> Option<i128> {
+        let mut square = CURVE_PRECISION / 10000 * (10000 + rate_bps as i128);
+        let mut factor = CURVE_PRECISION;
+        let mut exponent = minted;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                factor = Self::mul_fixed(factor, square)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = Self::mul_fixed(square, square)?;
+            }
+        }
+        Some(factor)
+    }
+
+    fn mul_fixed(a: i128, b: i128) -> Option<i128> {
+        a.checked_mul(b).map(|product| product / CURVE_PRECISION)
+    }
+
+    // Step price of `auction` at `now`
+    fn auction_price(auction: &DutchAuction, now: u64) -> i128 {
+        if now <= auction.start_time {
+            return auction.start_price;
+        }
+        if now >= auction.end_time {
+            return auction.reserve_price;
+        }
+        let total_steps = (auction.end_time - auction.start_time).div_ceil(auction.step_seconds);
+        let steps = (now - auction.start_time) / auction.step_seconds;
+        let drop = auction.start_price - auction.reserve_price;
+        auction.start_price - drop * steps as i128 / total_steps as i128
+    }
+
+    /// Admin-only: cap how many tickets of `tier_symbol` a single wallet may
+    /// mint (`0` disables the cap).  When `apply_to_batch_mint` is set the cap
+    /// is also enforced on organizer `batch_mint` calls.
+    pub fn set_wallet_cap(
+        e: &Env,
+        tier_symbol: Symbol,
+        max_per_wallet: u32,
+        apply_to_batch_mint: bool,
+    ) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Tier(tier_symbol);
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.max_per_wallet = max_per_wallet;
+        tier.cap_batch_mint = apply_to_batch_mint;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Number of tickets of `tier_symbol` minted to `owner` so far.
+    pub fn wallet_minted(e: &Env, tier_symbol: Symbol, owner: Address) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::WalletMinted(tier_symbol, owner))
+            .unwrap_or(0)
+    }
+
+    // Track tickets minted to `owner` and enforce the tier's per-wallet cap
+    fn record_wallet_mint(e: &Env, tier_symbol: &Symbol, tier: &Tier, owner: &Address, qty: u32) {
+        let key = DataKey::WalletMinted(tier_symbol.clone(), owner.clone());
+        let minted: u32 = e.storage().persistent().get(&key).unwrap_or(0);
+        if tier.max_per_wallet > 0 && minted + qty > tier.max_per_wallet {
+            panic!("Exceeds per-wallet limit");
+        }
+        e.storage().persistent().set(&key, &(minted + qty));
+    }
+
+    // Mint a single ticket and store its record
+    fn mint_ticket(
+        e: &Env,
+        to: &Address,
+        tier_symbol: &Symbol,
+        price_paid: i128,
+        payment_token: Option<Address>,
+    ) -> u32 {
+        let token_id = Base::sequential_mint(e, to);
+
+        let ticket = Ticket {
+            tier_symbol: tier_symbol.clone(),
+            purchase_time: e.ledger().timestamp(),
+            price_paid,
+            is_valid: true,
+            payment_token,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::Ticket(token_id), &ticket);
+
+        enumeration::add(e, &TicketIndex::Owner(to.clone()), token_id);
+        enumeration::add(e, &TicketIndex::Tier(tier_symbol.clone()), token_id);
+        let supply = Self::total_supply(e);
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(supply + 1));
+        token_id
+    }
+
+    // Batch Minting for Organizer
+    pub fn batch_mint(e: &Env, to: Address, tier_symbol: Symbol, amount: u32) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+        if Self::seats_taken(e, &tier_symbol, &tier) + amount > tier.max_supply {
+            panic!("Exceeds tier max supply");
+        }
+        if tier.cap_batch_mint {
+            Self::record_wallet_mint(e, &tier_symbol, &tier, &to, amount);
+        }
+
+        for _ in 0..amount {
+            Self::mint_ticket(e, &to, &tier_symbol, 0, None); // Admin mints are free
+        }
+
+        tier.minted += amount;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Lock the current price of `tier_symbol` for `buyer`, paying in
+    /// `payment_token`, for `QUOTE_TTL_SECONDS`.
+    ///
+    /// The quote is kept in temporary storage (one outstanding quote per buyer)
+    /// and is consumed by `purchase_with_quote`, so the UI can show a firm price
+    /// even though the oracle multiplier, demand thresholds and (for fiat
+    /// tiers) the token's exchange rate keep moving.
+    pub fn quote_price(
+        e: &Env,
+        buyer: Address,
+        tier_symbol: Symbol,
+        payment_token: Address,
+    ) -> PriceQuote {
+        buyer.require_auth();
+
+        let price = Self::price_for(e, &tier_symbol, Some(&buyer), true);
+        let amount = Self::to_token_amounts(e, &tier_symbol, &payment_token, &vec![e, price], true)
+            .get_unchecked(0);
+        let quote = PriceQuote {
+            tier_symbol,
+            price,
+            payment_token,
+            amount,
+            expires_at: e.ledger().timestamp() + QUOTE_TTL_SECONDS,
+        };
+
+        let key = DataKey::Quote(buyer);
+        e.storage().temporary().set(&key, &quote);
+        e.storage()
+            .temporary()
+            .extend_ttl(&key, QUOTE_TTL_LEDGERS, QUOTE_TTL_LEDGERS);
+        quote
+    }
+
+    /// Outstanding (possibly expired) quote held by `buyer`, if any.
+    pub fn get_quote(e: &Env, buyer: Address) -> Option<PriceQuote> {
+        e.storage().temporary().get(&DataKey::Quote(buyer))
+    }
+
+    // Purchase a ticket, failing if the execution price exceeds `max_price`
+    // (in `payment_token` units).  An optional promo code preimage is applied
+    // after dynamic pricing.
+    pub fn purchase(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        max_price: i128,
+        promo_code: Option<Bytes>,
+    ) {
+        buyer.require_auth();
+
+        let price = Self::price_for(e, &tier_symbol, Some(&buyer), true);
+        let charged = match promo_code {
+            Some(code) => Self::redeem_promo_code(e, &code, &tier_symbol, price),
+            None => price,
+        };
+        let amounts =
+            Self::to_token_amounts(e, &tier_symbol, &payment_token, &vec![e, charged], true);
+        if amounts.get_unchecked(0) > max_price {
+            panic!("Price exceeds max_price");
+        }
+
+        Self::complete_purchase(
+            e,
+            &buyer,
+            &payment_token,
+            &tier_symbol,
+            vec![e, price],
+            amounts,
+            false,
+            Some(e.ledger().timestamp()),
+        );
+    }
+
+    /// Admin-only: register a promo code by the sha256 hash of its text, so
+    /// codes are not readable from contract storage before they are used.
+    /// `tier` restricts the code to one tier; `None` makes it valid for all.
+    pub fn add_promo_code(
+        e: &Env,
+        code_hash: BytesN<32>,
+        discount: Discount,
+        max_uses: u32,
+        expires_at: u64,
+        tier: Option<Symbol>,
+    ) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        match discount {
+            Discount::Percent(bps) if bps > 10000 => panic!("Discount exceeds 100%"),
+            Discount::Fixed(amount) if amount < 0 => panic!("Discount must not be negative"),
+            _ => {}
+        }
+        if max_uses == 0 {
+            panic!("Promo code needs at least one use");
+        }
+
+        let key = DataKey::PromoCode(code_hash);
+        if e.storage().persistent().has(&key) {
+            panic!("Promo code already exists");
+        }
+        let promo = PromoCode {
+            discount,
+            max_uses,
+            uses: 0,
+            expires_at,
+            tier,
+        };
+        e.storage().persistent().set(&key, &promo);
+    }
+
+    /// Admin-only: withdraw a promo code before it runs out or expires.
+    pub fn remove_promo_code(e: &Env, code_hash: BytesN<32>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        e.storage()
+            .persistent()
+            .remove(&DataKey::PromoCode(code_hash));
+    }
+
+    pub fn get_promo_code(e: &Env, code_hash: BytesN<32>) -> Option<PromoCode> {
+        e.storage().persistent().get(&DataKey::PromoCode(code_hash))
+    }
+
+    // Validate `code`, record its use and return the discounted `price`.  The
+    // discount never takes the price below the tier's price floor (or raises
+    // it, if the undiscounted price was already under the floor).
+    fn redeem_promo_code(e: &Env, code: &Bytes, tier_symbol: &Symbol, price: i128) -> i128 {
+        let key = DataKey::PromoCode(e.crypto().sha256(code).to_bytes());
+        let mut promo: PromoCode = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Invalid promo code"));
+
+        if e.ledger().timestamp() > promo.expires_at {
+            panic!("Promo code expired");
+        }
+        if promo.uses >= promo.max_uses {
+            panic!("Promo code exhausted");
+        }
+        if let Some(tier) = &promo.tier {
+            if tier != tier_symbol {
+                panic!("Promo code not valid for tier");
+            }
+        }
+
+        let discounted = match promo.discount {
+            Discount::Percent(bps) => price - price * bps as i128 / 10000,
+            Discount::Fixed(amount) => price - amount,
+        };
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(tier_symbol.clone()))
+            .unwrap();
+        let floor = Self::price_bounds(e, &config, tier_symbol, &tier).floor;
+
+        promo.uses += 1;
+        e.storage().persistent().set(&key, &promo);
+
+        discounted.max(floor).min(price)
+    }
+
+    /// Purchase `qty` tickets of one tier in a single call, failing if the
+    /// summed price along the demand curve exceeds `max_total` (in
+    /// `payment_token` units).
+    pub fn purchase_many(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        qty: u32,
+        max_total: i128,
+    ) {
+        buyer.require_auth();
+
+        if qty == 0 || qty > MAX_PURCHASE_QUANTITY {
+            panic!("Invalid quantity");
+        }
+
+        let prices = Self::unit_prices(e, &tier_symbol, Some(&buyer), qty, true);
+        let amounts = Self::to_token_amounts(e, &tier_symbol, &payment_token, &prices, true);
+        let mut total: i128 = 0;
+        for amount in amounts.iter() {
+            total += amount;
+        }
+        if total > max_total {
+            panic!("Total exceeds max_total");
+        }
+
+        Self::complete_purchase(
+            e,
+            &buyer,
+            &payment_token,
+            &tier_symbol,
+            prices,
+            amounts,
+            false,
+            Some(e.ledger().timestamp()),
+        );
+    }
+
+    /// Purchase a ticket at the price locked by a previous `quote_price` call.
+    pub fn purchase_with_quote(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+    ) {
+        buyer.require_auth();
+
+        let key = DataKey::Quote(buyer.clone());
+        let quote: PriceQuote = e
+            .storage()
+            .temporary()
+            .get(&key)
+            .unwrap_or_else(|| panic!("No quote found"));
+        if quote.tier_symbol != tier_symbol {
+            panic!("Quote is for a different tier");
+        }
+        if quote.payment_token != payment_token {
+            panic!("Quote is for a different payment token");
+        }
+        if e.ledger().timestamp() > quote.expires_at {
+            panic!("Quote expired");
+        }
+        e.storage().temporary().remove(&key);
+
+        // The quoted price is the auction step current when it was quoted
+        Self::complete_purchase(
+            e,
+            &buyer,
+            &payment_token,
+            &tier_symbol,
+            vec![e, quote.price],
+            vec![e, quote.amount],
+            false,
+            Some(quote.expires_at - QUOTE_TTL_SECONDS),
+        );
+    }
+
+    /// Admin-only: run a presale for `tier_symbol` restricted to the
+    /// addresses committed to by `presale.merkle_root` (see `merkle.rs`).
+    /// While the presale window is open the public sale of the tier is closed.
+    pub fn set_presale(e: &Env, tier_symbol: Symbol, presale: Presale) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if presale.end <= presale.start {
+            panic!("Presale end must be after presale start");
+        }
+        if !e
+            .storage()
+            .persistent()
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
+        }
+        e.storage()
+            .persistent()
+            .set(&DataKey::Presale(tier_symbol), &presale);
+    }
+
+    pub fn get_presale(e: &Env, tier_symbol: Symbol) -> Option<Presale> {
+        e.storage().persistent().get(&DataKey::Presale(tier_symbol))
+    }
+
+    /// Presale tickets of `tier_symbol` already bought by `buyer`.
+    pub fn presale_used(e: &Env, tier_symbol: Symbol, buyer: Address) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::PresaleUsed(tier_symbol, buyer))
+            .unwrap_or(0)
+    }
+
+    /// Buy `qty` presale tickets.  `allocation` and `proof` must prove that
+    /// the leaf `(buyer, allocation)` is in the tier's presale Merkle tree; a
+    /// non-zero allocation caps the presale tickets this buyer can take.
+    #[allow(clippy::too_many_arguments)]
+    pub fn purchase_presale(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        qty: u32,
+        max_total: i128,
+        allocation: u32,
+        proof: Vec<BytesN<32>>,
+    ) {
+        buyer.require_auth();
+
+        if qty == 0 || qty > MAX_PURCHASE_QUANTITY {
+            panic!("Invalid quantity");
+        }
+
+        let presale = Self::get_presale(e, tier_symbol.clone())
+            .unwrap_or_else(|| panic!("No presale for tier"));
+        let now = e.ledger().timestamp();
+        if now < presale.start || now >= presale.end {
+            panic!("Presale not active");
+        }
+
+        let leaf = merkle::leaf_hash(e, &buyer, allocation);
+        if !merkle::verify(e, &presale.merkle_root, &proof, leaf) {
+            panic!("Invalid presale proof");
+        }
+
+        let used = Self::presale_used(e, tier_symbol.clone(), buyer.clone());
+        if allocation > 0 && used + qty > allocation {
+            panic!("Exceeds presale allocation");
+        }
+
+        let prices = Self::unit_prices(e, &tier_symbol, Some(&buyer), qty, true);
+        let amounts = Self::to_token_amounts(e, &tier_symbol, &payment_token, &prices, true);
+        let mut total: i128 = 0;
+        for amount in amounts.iter() {
+            total += amount;
+        }
+        if total > max_total {
+            panic!("Total exceeds max_total");
+        }
+
+        Self::complete_purchase(
+            e,
+            &buyer,
+            &payment_token,
+            &tier_symbol,
+            prices,
+            amounts,
+            true,
+            Some(e.ledger().timestamp()),
+        );
+        e.storage()
+            .persistent()
+            .set(&DataKey::PresaleUsed(tier_symbol, buyer), &(used + qty));
+    }
+
+    // Whether the tier's presale window is open (public sales are paused)
+    fn presale_live(e: &Env, tier_symbol: &Symbol) -> bool {
+        match Self::get_presale(e, tier_symbol.clone()) {
+            Some(presale) => {
+                let now = e.ledger().timestamp();
+                now >= presale.start && now < presale.end
+            }
+            None => false,
+        }
+    }
+
+    /// Admin-only: sell `tier_symbol` by lottery.  During `[start, end)`
+    /// buyers enter with a deposit of `price` in `payment_token`; once the
+    /// window closes `settle_lottery` draws the winners and the public sale
+    /// stays closed until it does.  Winners then have `claim_window` seconds
+    /// to claim before their seats go back on sale.
+    pub fn set_lottery(
+        e: &Env,
+        tier_symbol: Symbol,
+        payment_token: Address,
+        price: i128,
+        start: u64,
+        end: u64,
+        claim_window: u64,
+    ) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if end <= start {
+            panic!("Lottery end must be after lottery start");
+        }
+        if price <= 0 {
+            panic!("Invalid price");
+        }
+        if claim_window == 0 {
+            panic!("Invalid claim window");
+        }
+        if !e
+            .storage()
+            .persistent()
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
+        }
+        if let Some(existing) = Self::get_lottery(e, tier_symbol.clone()) {
+            if existing.entrants > 0 {
+                panic!("Lottery already has entrants");
+            }
+        }
+
+        let lottery = Lottery {
+            payment_token,
+            price,
+            start,
+            end,
+            claim_window,
+            entrants: 0,
+            winners: 0,
+            drawn: 0,
+            settled: false,
+            claim_deadline: 0,
+            claimed: 0,
+            released: false,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::Lottery(tier_symbol), &lottery);
+    }
+
+    pub fn get_lottery(e: &Env, tier_symbol: Symbol) -> Option<Lottery> {
+        e.storage().persistent().get(&DataKey::Lottery(tier_symbol))
+    }
+
+    /// Enter the lottery of `tier_symbol` (one entry per address), escrowing
+    /// the full ticket price until the draw.
+    pub fn enter_lottery(e: &Env, buyer: Address, tier_symbol: Symbol) {
+        buyer.require_auth();
+
+        let lottery_key = DataKey::Lottery(tier_symbol.clone());
+        let mut lottery: Lottery = e
+            .storage()
+            .persistent()
+            .get(&lottery_key)
+            .unwrap_or_else(|| panic!("No lottery for tier"));
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+        let now = e.ledger().timestamp();
+        if now < lottery.start || now >= lottery.end {
+            panic!("Lottery not open");
+        }
+        let entry_key = DataKey::LotteryEntry(tier_symbol.clone(), buyer.clone());
+        if e.storage().persistent().has(&entry_key) {
+            panic!("Already entered");
+        }
+
+        let token_client = token::Client::new(e, &lottery.payment_token);
+        token_client.transfer(&buyer, e.current_contract_address(), &lottery.price);
+
+        e.storage().persistent().set(
+            &DataKey::LotteryEntrant(tier_symbol, lottery.entrants),
+            &buyer,
+        );
+        e.storage().persistent().set(&entry_key, &false);
+        lottery.entrants += 1;
+        e.storage().persistent().set(&lottery_key, &lottery);
+    }
+
+    /// Admin-only: draw up to `max_draws` winners once the entry window has
+    /// closed, returning how many were drawn.  Every remaining seat of the
+    /// tier is awarded; if the tier is oversubscribed the winners are picked
+    /// with the ledger PRNG, so the draw is restricted to the admin rather
+    /// than left to entrants who could retry until they win.  Large draws
+    /// span several calls; winning seats are held back from the public sale
+    /// and claims open once the last winner is drawn.
+    pub fn settle_lottery(e: &Env, tier_symbol: Symbol, max_draws: u32) -> u32 {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let lottery_key = DataKey::Lottery(tier_symbol.clone());
+        let mut lottery: Lottery = e
+            .storage()
+            .persistent()
+            .get(&lottery_key)
+            .unwrap_or_else(|| panic!("No lottery for tier"));
+        if lottery.settled {
+            panic!("Lottery already settled");
+        }
+        if e.ledger().timestamp() < lottery.end {
+            panic!("Lottery still open");
+        }
+
+        // The first call fixes the number of seats awarded and holds them back
+        if lottery.drawn == 0 {
+            let tier_key = DataKey::Tier(tier_symbol.clone());
+            let mut tier: Tier = e.storage().persistent().get(&tier_key).unwrap();
+            let seats = tier.max_supply - Self::seats_taken(e, &tier_symbol, &tier);
+            lottery.winners = lottery.entrants.min(seats);
+            tier.reserved += lottery.winners;
+            e.storage().persistent().set(&tier_key, &tier);
+        }
+
+        // Partial Fisher-Yates: after step i the first i + 1 slots are winners
+        let count = lottery.entrants;
+        let end = lottery.winners.min(lottery.drawn.saturating_add(max_draws));
+        let drawn = end - lottery.drawn;
+        for i in lottery.drawn..end {
+            let j: u64 = e.prng().gen_range(i as u64..count as u64);
+            let j = j as u32;
+            let slot_i = DataKey::LotteryEntrant(tier_symbol.clone(), i);
+            let winner: Address = if j != i {
+                let slot_j = DataKey::LotteryEntrant(tier_symbol.clone(), j);
+                let winner: Address = e.storage().persistent().get(&slot_j).unwrap();
+                let displaced: Address = e.storage().persistent().get(&slot_i).unwrap();
+                e.storage().persistent().set(&slot_j, &displaced);
+                e.storage().persistent().set(&slot_i, &winner);
+                winner
+            } else {
+                e.storage().persistent().get(&slot_i).unwrap()
+            };
+            e.storage()
+                .persistent()
+                .set(&DataKey::LotteryEntry(tier_symbol.clone(), winner), &true);
+        }
+        lottery.drawn = end;
+
+        if lottery.drawn == lottery.winners {
+            lottery.settled = true;
+            lottery.claim_deadline = e.ledger().timestamp() + lottery.claim_window;
+
+            LotterySettled {
+                tier_symbol,
+                entrants: count,
+                winners: lottery.winners,
+            }
+            .publish(e);
+        }
+        e.storage().persistent().set(&lottery_key, &lottery);
+        drawn
+    }
+
+    /// Whether `buyer` entered the lottery of `tier_symbol` and, after the
+    /// draw, whether they won.  None if they have no unclaimed entry.
+    pub fn lottery_entry(e: &Env, tier_symbol: Symbol, buyer: Address) -> Option<bool> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::LotteryEntry(tier_symbol, buyer))
+    }
+
+    /// Settle `buyer`'s lottery entry: winners claiming before the deadline
+    /// get their ticket minted at the lottery price, everyone else (or
+    /// everyone, if the event was cancelled) gets the deposit back.  Returns
+    /// whether a ticket was minted.
+    pub fn claim_lottery(e: &Env, buyer: Address, tier_symbol: Symbol) -> bool {
+        buyer.require_auth();
+
+        let lottery_key = DataKey::Lottery(tier_symbol.clone());
+        let mut lottery: Lottery = e
+            .storage()
+            .persistent()
+            .get(&lottery_key)
+            .unwrap_or_else(|| panic!("No lottery for tier"));
+        let cancelled = Self::is_cancelled(e);
+        if !lottery.settled && !cancelled {
+            panic!("Lottery not settled");
+        }
+        let entry_key = DataKey::LotteryEntry(tier_symbol.clone(), buyer.clone());
+        let won: bool = e
+            .storage()
+            .persistent()
+            .get(&entry_key)
+            .unwrap_or_else(|| panic!("No lottery entry"));
+        e.storage().persistent().remove(&entry_key);
+
+        // Past the deadline the seat is (or is about to be) released instead
+        let lapsed = lottery.settled && e.ledger().timestamp() > lottery.claim_deadline;
+        let seated = won && !lapsed;
+
+        let tier_key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e.storage().persistent().get(&tier_key).unwrap();
+        if seated {
+            tier.reserved -= 1;
+            lottery.claimed += 1;
+            e.storage().persistent().set(&lottery_key, &lottery);
+        }
+
+        if seated && !cancelled {
+            Self::record_wallet_mint(e, &tier_symbol, &tier, &buyer, 1);
+
+            let mut totals = Self::get_escrow_totals(e, lottery.payment_token.clone());
+            totals.collected += lottery.price;
+            e.storage()
+                .persistent()
+                .set(&DataKey::Escrow(lottery.payment_token.clone()), &totals);
+
+            Self::mint_ticket(
+                e,
+                &buyer,
+                &tier_symbol,
+                lottery.price,
+                Some(lottery.payment_token),
+            );
+            tier.minted += 1;
+        } else {
+            let token_client = token::Client::new(e, &lottery.payment_token);
+            token_client.transfer(&e.current_contract_address(), &buyer, &lottery.price);
+        }
+        e.storage().persistent().set(&tier_key, &tier);
+        seated && !cancelled
+    }
+
+    /// Put the seats of winners who missed the claim deadline back on sale
+    /// (offering them to the waitlist first).  Callable by anyone; returns
+    /// the number of seats released.  Late winners can still reclaim their
+    /// deposit through `claim_lottery`.
+    pub fn release_lottery_seats(e: &Env, tier_symbol: Symbol) -> u32 {
+        let lottery_key = DataKey::Lottery(tier_symbol.clone());
+        let mut lottery: Lottery = e
+            .storage()
+            .persistent()
+            .get(&lottery_key)
+            .unwrap_or_else(|| panic!("No lottery for tier"));
+        if !lottery.settled || e.ledger().timestamp() <= lottery.claim_deadline {
+            panic!("Claim window open");
+        }
+        if lottery.released {
+            return 0;
+        }
+
+        let tier_key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e.storage().persistent().get(&tier_key).unwrap();
+        let unclaimed = lottery.winners - lottery.claimed;
+        tier.reserved -= unclaimed;
+        for _ in 0..unclaimed {
+            Self::release_seat(e, &tier_symbol, &mut tier);
+        }
+        e.storage().persistent().set(&tier_key, &tier);
+        lottery.released = true;
+        e.storage().persistent().set(&lottery_key, &lottery);
+        unclaimed
+    }
+
+    // Whether a lottery of the tier has started and not yet been drawn
+    // (public sales are paused)
+    fn lottery_pending(e: &Env, tier_symbol: &Symbol) -> bool {
+        match Self::get_lottery(e, tier_symbol.clone()) {
+            Some(lottery) => !lottery.settled && e.ledger().timestamp() >= lottery.start,
+            None => false,
+        }
+    }
+
+    /// Admin-only: configure the waitlist of `tier_symbol`.  Joining costs
+    /// `deposit` of `deposit_token` (refunded on claim or leave, forfeited to
+    /// the organizer when an offer lapses) and offers stay open for
+    /// `offer_window` seconds.
+    pub fn set_waitlist_config(e: &Env, tier_symbol: Symbol, config: WaitlistConfig) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if !e
+            .storage()
+            .persistent()
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
+        }
+        if config.deposit < 0 || (config.deposit > 0 && config.deposit_token.is_none()) {
+            panic!("Invalid deposit");
+        }
+        if config.offer_window == 0 {
+            panic!("Invalid offer window");
+        }
+        e.storage()
+            .persistent()
+            .set(&DataKey::WaitlistConfig(tier_symbol), &config);
+    }
+
+    pub fn get_waitlist_config(e: &Env, tier_symbol: Symbol) -> WaitlistConfig {
+        e.storage()
+            .persistent()
+            .get(&DataKey::WaitlistConfig(tier_symbol))
+            .unwrap_or(WaitlistConfig {
+                deposit_token: None,
+                deposit: 0,
+                offer_window: DEFAULT_WAITLIST_OFFER_SECONDS,
+            })
+    }
+
+    /// Queue up for a sold-out tier.  When a seat frees up the next address
+    /// in line gets an exclusive, time-limited offer to buy it.
+    pub fn join_waitlist(e: &Env, buyer: Address, tier_symbol: Symbol) {
+        buyer.require_auth();
+
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(tier_symbol.clone()))
+            .unwrap_or_else(|| panic!("Tier not found"));
+        if Self::seats_taken(e, &tier_symbol, &tier) < tier.max_supply {
+            panic!("Tier not sold out");
+        }
+        let spot_key = DataKey::WaitlistSpot(tier_symbol.clone(), buyer.clone());
+        if e.storage().persistent().has(&spot_key) {
+            panic!("Already on waitlist");
+        }
+
+        let config = Self::get_waitlist_config(e, tier_symbol.clone());
+        if let Some(deposit_token) = &config.deposit_token {
+            if config.deposit > 0 {
+                let token_client = token::Client::new(e, deposit_token);
+                token_client.transfer(&buyer, e.current_contract_address(), &config.deposit);
+            }
+        }
+
+        let mut queue = Self::waitlist_queue(e, &tier_symbol);
+        let position = queue.next_position;
+        let spot = WaitlistSpot {
+            position,
+            deposit_token: config.deposit_token,
+            deposit: config.deposit,
+            offer_expires_at: 0,
+        };
+        e.storage().persistent().set(
+            &DataKey::WaitlistEntry(tier_symbol.clone(), position),
+            &WaitlistNode {
+                holder: buyer,
+                prev: queue.tail,
+                next: None,
+            },
+        );
+        match queue.tail {
+            Some(tail) => {
+                let tail_key = DataKey::WaitlistEntry(tier_symbol.clone(), tail);
+                let mut node: WaitlistNode = e.storage().persistent().get(&tail_key).unwrap();
+                node.next = Some(position);
+                e.storage().persistent().set(&tail_key, &node);
+            }
+            None => queue.head = Some(position),
+        }
+        e.storage().persistent().set(&spot_key, &spot);
+        queue.tail = Some(position);
+        queue.next_position += 1;
+        queue.length += 1;
+        e.storage()
+            .persistent()
+            .set(&DataKey::WaitlistQueue(tier_symbol), &queue);
+    }
+
+    /// Leave the waitlist (or decline an outstanding offer) and get the
+    /// deposit back, unless the offer has already lapsed, in which case the
+    /// deposit is forfeited as in `roll_waitlist`.  A declined seat is
+    /// offered to the next address in line.
+    pub fn leave_waitlist(e: &Env, buyer: Address, tier_symbol: Symbol) {
+        buyer.require_auth();
+
+        let spot_key = DataKey::WaitlistSpot(tier_symbol.clone(), buyer.clone());
+        let spot: WaitlistSpot = e
+            .storage()
+            .persistent()
+            .get(&spot_key)
+            .unwrap_or_else(|| panic!("Not on waitlist"));
+        e.storage().persistent().remove(&spot_key);
+        if spot.offer_expires_at != 0 && e.ledger().timestamp() > spot.offer_expires_at {
+            Self::forfeit_deposit(e, &spot);
+        } else {
+            Self::return_deposit(e, &buyer, &spot);
+        }
+
+        if spot.offer_expires_at == 0 {
+            let mut queue = Self::waitlist_queue(e, &tier_symbol);
+            Self::unlink_waitlist_entry(e, &tier_symbol, &mut queue, spot.position);
+            e.storage()
+                .persistent()
+                .set(&DataKey::WaitlistQueue(tier_symbol), &queue);
+        } else {
+            let key = DataKey::Tier(tier_symbol.clone());
+            let mut tier: Tier = e.storage().persistent().get(&key).unwrap();
+            Self::withdraw_offer(e, &tier_symbol, &buyer);
+            tier.reserved -= 1;
+            Self::release_seat(e, &tier_symbol, &mut tier);
+            e.storage().persistent().set(&key, &tier);
+        }
+    }
+
+    /// Buy the seat offered to `buyer` at the tier's current price.
+    pub fn claim_waitlist_offer(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        max_price: i128,
+    ) {
+        buyer.require_auth();
+
+        let spot_key = DataKey::WaitlistSpot(tier_symbol.clone(), buyer.clone());
+        let spot: WaitlistSpot = e
+            .storage()
+            .persistent()
+            .get(&spot_key)
+            .unwrap_or_else(|| panic!("No waitlist offer"));
+        if spot.offer_expires_at == 0 {
+            panic!("No waitlist offer");
+        }
+        if e.ledger().timestamp() > spot.offer_expires_at {
+            panic!("Offer expired");
+        }
+
+        let prices = vec![e, Self::price_for(e, &tier_symbol, Some(&buyer), true)];
+        let amounts = Self::to_token_amounts(e, &tier_symbol, &payment_token, &prices, true);
+        if amounts.get_unchecked(0) > max_price {
+            panic!("Price exceeds max_price");
+        }
+
+        // Hand the reserved seat back so `complete_purchase` can sell it
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e.storage().persistent().get(&key).unwrap();
+        tier.reserved -= 1;
+        e.storage().persistent().set(&key, &tier);
+        Self::withdraw_offer(e, &tier_symbol, &buyer);
+        e.storage().persistent().remove(&spot_key);
+        Self::return_deposit(e, &buyer, &spot);
+
+        Self::complete_purchase(
+            e,
+            &buyer,
+            &payment_token,
+            &tier_symbol,
+            prices,
+            amounts,
+            true,
+            None,
+        );
+    }
+
+    /// Expire lapsed offers of `tier_symbol`, forfeiting their deposits and
+    /// rolling each seat to the next address in line.  Callable by anyone;
+    /// returns the number of offers that lapsed.
+    pub fn roll_waitlist(e: &Env, tier_symbol: Symbol) -> u32 {
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        let mut queue = Self::waitlist_queue(e, &tier_symbol);
+        let now = e.ledger().timestamp();
+
+        let mut live = Vec::new(e);
+        let mut lapsed = 0u32;
+        for holder in queue.offers.iter() {
+            let spot_key = DataKey::WaitlistSpot(tier_symbol.clone(), holder.clone());
+            let spot: WaitlistSpot = e.storage().persistent().get(&spot_key).unwrap();
+            if now <= spot.offer_expires_at {
+                live.push_back(holder);
+                continue;
+            }
+            e.storage().persistent().remove(&spot_key);
+            Self::forfeit_deposit(e, &spot);
+            tier.reserved -= 1;
+            lapsed += 1;
+        }
+        queue.offers = live;
+        e.storage()
+            .persistent()
+            .set(&DataKey::WaitlistQueue(tier_symbol.clone()), &queue);
+
+        for _ in 0..lapsed {
+            Self::release_seat(e, &tier_symbol, &mut tier);
+        }
+        e.storage().persistent().set(&key, &tier);
+        lapsed
+    }
+
+    pub fn get_waitlist_spot(e: &Env, tier_symbol: Symbol, owner: Address) -> Option<WaitlistSpot> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::WaitlistSpot(tier_symbol, owner))
+    }
+
+    /// Number of addresses waiting in line (not yet offered a seat).
+    pub fn waitlist_length(e: &Env, tier_symbol: Symbol) -> u32 {
+        Self::waitlist_queue(e, &tier_symbol).length
+    }
+
+    fn waitlist_queue(e: &Env, tier_symbol: &Symbol) -> WaitlistQueue {
+        e.storage()
+            .persistent()
+            .get(&DataKey::WaitlistQueue(tier_symbol.clone()))
+            .unwrap_or(WaitlistQueue {
+                head: None,
+                tail: None,
+                next_position: 0,
+                length: 0,
+                offers: Vec::new(e),
+            })
+    }
+
+    // Take the entry at `position` out of line, relinking its neighbours, and
+    // return its holder.  The caller persists `queue`.
+    fn unlink_waitlist_entry(
+        e: &Env,
+        tier_symbol: &Symbol,
+        queue: &mut WaitlistQueue,
+        position: u32,
+    ) -> Address {
+        let entry_key = DataKey::WaitlistEntry(tier_symbol.clone(), position);
+        let node: WaitlistNode = e.storage().persistent().get(&entry_key).unwrap();
+        e.storage().persistent().remove(&entry_key);
+
+        match node.prev {
+            Some(prev) => {
+                let prev_key = DataKey::WaitlistEntry(tier_symbol.clone(), prev);
+                let mut prev_node: WaitlistNode = e.storage().persistent().get(&prev_key).unwrap();
+                prev_node.next = node.next;
+                e.storage().persistent().set(&prev_key, &prev_node);
+            }
+            None => queue.head = node.next,
+        }
+        match node.next {
+            Some(next) => {
+                let next_key = DataKey::WaitlistEntry(tier_symbol.clone(), next);
+                let mut next_node: WaitlistNode = e.storage().persistent().get(&next_key).unwrap();
+                next_node.prev = node.prev;
+                e.storage().persistent().set(&next_key, &next_node);
+            }
+            None => queue.tail = node.prev,
+        }
+        queue.length -= 1;
+        node.holder
+    }
+
+    // A seat of `tier` became free: offer it to the next address in line and
+    // hold it back from public sale.  With nobody waiting, or once the event
+    // is cancelled, it simply goes back on sale.  The caller persists `tier`.
+    fn release_seat(e: &Env, tier_symbol: &Symbol, tier: &mut Tier) {
+        if Self::is_cancelled(e) {
+            return;
+        }
+        let mut queue = Self::waitlist_queue(e, tier_symbol);
+        let Some(position) = queue.head else {
+            return;
+        };
+        let config = Self::get_waitlist_config(e, tier_symbol.clone());
+
+        let holder = Self::unlink_waitlist_entry(e, tier_symbol, &mut queue, position);
+        let spot_key = DataKey::WaitlistSpot(tier_symbol.clone(), holder.clone());
+        let mut spot: WaitlistSpot = e.storage().persistent().get(&spot_key).unwrap();
+        spot.offer_expires_at = e.ledger().timestamp() + config.offer_window;
+        e.storage().persistent().set(&spot_key, &spot);
+        queue.offers.push_back(holder.clone());
+        tier.reserved += 1;
+
+        WaitlistOffer {
+            tier_symbol: tier_symbol.clone(),
+            holder,
+            offer_expires_at: spot.offer_expires_at,
+        }
+        .publish(e);
+        e.storage()
+            .persistent()
+            .set(&DataKey::WaitlistQueue(tier_symbol.clone()), &queue);
+    }
+
+    fn withdraw_offer(e: &Env, tier_symbol: &Symbol, holder: &Address) {
+        let mut queue = Self::waitlist_queue(e, tier_symbol);
+        if let Some(index) = queue.offers.first_index_of(holder) {
+            queue.offers.remove(index);
+        }
+        e.storage()
+            .persistent()
+            .set(&DataKey::WaitlistQueue(tier_symbol.clone()), &queue);
+    }
+
+    fn return_deposit(e: &Env, holder: &Address, spot: &WaitlistSpot) {
+        if let Some(deposit_token) = &spot.deposit_token {
+            if spot.deposit > 0 {
+                let token_client = token::Client::new(e, deposit_token);
+                token_client.transfer(&e.current_contract_address(), holder, &spot.deposit);
+            }
+        }
+    }
+
+    // A lapsed offer's deposit is kept as retained proceeds.
+    fn forfeit_deposit(e: &Env, spot: &WaitlistSpot) {
+        if let Some(deposit_token) = &spot.deposit_token {
+            if spot.deposit > 0 {
+                let mut totals = Self::get_escrow_totals(e, deposit_token.clone());
+                totals.collected += spot.deposit;
+                totals.retained += spot.deposit;
+                e.storage()
+                    .persistent()
+                    .set(&DataKey::Escrow(deposit_token.clone()), &totals);
+            }
+        }
+    }
+
+    // Seats of `tier` held by tickets in circulation or by waitlist offers.
+    // Refunded seats are free again, though `minted` (which drives pricing)
+    // keeps counting them.
+    fn seats_taken(e: &Env, tier_symbol: &Symbol, tier: &Tier) -> u32 {
+        enumeration::len(e, &TicketIndex::Tier(tier_symbol.clone())) + tier.reserved
+    }
+
+    // Shared settlement for the purchase entrypoints: one ticket is minted per
+    // entry in `prices` (list prices in the tier's unit), each charged and
+    // recording the matching entry of `amounts` (in `payment_token` units
+    // after any promo discount, see `to_token_amounts`).  With `own_window` the caller has already applied
+    // its own window (presale allowlist, waitlist offer) in place of the
+    // public sale gates.  `priced_at` is when `prices` were read, for auction
+    // step tracking; None for sales not made at an auction step.
+    #[allow(clippy::too_many_arguments)]
+    fn complete_purchase(
+        e: &Env,
+        buyer: &Address,
+        payment_token: &Address,
+        tier_symbol: &Symbol,
+        prices: Vec<i128>,
+        amounts: Vec<i128>,
+        own_window: bool,
+        priced_at: Option<u64>,
+    ) {
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+
+        let qty = prices.len();
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+        if !tier.active {
+            panic!("Tier is not active");
+        }
+        if !own_window {
+            let now = e.ledger().timestamp();
+            if now < tier.sale_start {
+                panic!("Sale has not started");
+            }
+            if tier.sale_end != 0 && now >= tier.sale_end {
+                panic!("Sale has ended");
+            }
+            if Self::presale_live(e, tier_symbol) {
+                panic!("Presale in progress");
+            }
+            if Self::lottery_pending(e, tier_symbol) {
+                panic!("Lottery in progress");
+            }
+        }
+        let taken = Self::seats_taken(e, tier_symbol, &tier);
+        if taken >= tier.max_supply {
+            panic!("Tier sold out");
+        }
+        if taken + qty > tier.max_supply {
+            panic!("Exceeds tier max supply");
+        }
+        Self::record_wallet_mint(e, tier_symbol, &tier, buyer, qty);
+
+        let mut total: i128 = 0;
+        for amount in amounts.iter() {
+            total += amount;
+        }
+
+        // Process payment into escrow
+        let token_client = token::Client::new(e, payment_token);
+        token_client.transfer(buyer, e.current_contract_address(), &total);
+        let mut totals = Self::get_escrow_totals(e, payment_token.clone());
+        totals.collected += total;
+        e.storage()
+            .persistent()
+            .set(&DataKey::Escrow(payment_token.clone()), &totals);
+
+        // Mint Tokens
+        let mut token_ids = Vec::new(e);
+        for (price, amount) in prices.iter().zip(amounts.iter()) {
+            let token_id =
+                Self::mint_ticket(e, buyer, tier_symbol, amount, Some(payment_token.clone()));
+            token_ids.push_back(token_id);
+            // Track the list price, not what a promo code brought it down to:
+            // it is what everyone pays while pricing is frozen
+            tier.current_price = price;
+        }
+
+        tier.minted += qty;
+        e.storage().persistent().set(&key, &tier);
+        Self::record_conversion(e, tier_symbol, buyer, payment_token, &token_ids, total);
+
+        Self::apply_experiment(e, tier_symbol, &mut tier, buyer);
+        if let (PricingStrategy::DutchAuction, Some(priced_at)) = (&tier.strategy, priced_at) {
+            // Track the clearing price: the lowest auction step sold at, before
+            // promo discounts and oracle multipliers.  Each ticket keeps its
+            // step so a rebate can be worked out in what it paid.
+            let auction_key = DataKey::DutchAuction(tier_symbol.clone());
+            let mut auction: DutchAuction = e.storage().persistent().get(&auction_key).unwrap();
+            let step_price = Self::auction_price(&auction, priced_at);
+            if auction.clearing_price == 0 || step_price < auction.clearing_price {
+                auction.clearing_price = step_price;
+            }
+            e.storage().persistent().set(&auction_key, &auction);
+            for token_id in token_ids.iter() {
+                e.storage()
+                    .persistent()
+                    .set(&DataKey::AuctionStep(token_id), &step_price);
+            }
+        }
+    }
+
+    // Refund a ticket from escrow.  The amount follows the refund schedule
+    // (see `preview_refund`); any remainder stays in escrow for the organizer.
+    // Once the event is cancelled checked-in tickets can be refunded too.
+    pub fn refund(e: &Env, owner: Address, payment_token: Address, token_id: u32) {
+        owner.require_auth();
+
+        let current_owner = Self::owner_of(e, token_id);
+        if owner != current_owner {
+            panic!("Not the ticket owner");
+        }
+
+        let mut ticket: Ticket = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
+            .unwrap();
+        if !ticket.is_valid {
+            panic!("Ticket already invalidated");
+        }
+
+        let cancelled = Self::is_cancelled(e);
+        let refund_bps = Self::refund_bps(e, &ticket.tier_symbol)
+            .unwrap_or_else(|| panic!("Refund window closed"));
+        if !cancelled && e.storage().persistent().has(&DataKey::CheckIn(token_id)) {
+            panic!("Ticket already used");
+        }
+
+        // Process refund
+        if ticket.price_paid > 0 {
+            if ticket.payment_token != Some(payment_token.clone()) {
+                panic!("Payment token mismatch");
+            }
+            let amount = ticket.price_paid * refund_bps as i128 / 10000;
+            if amount > 0 {
+                let token_client = token::Client::new(e, &payment_token);
+                token_client.transfer(&e.current_contract_address(), &owner, &amount);
+            }
+
+            let mut totals = Self::get_escrow_totals(e, payment_token.clone());
+            totals.refunded += amount;
+            totals.retained += ticket.price_paid - amount;
+            e.storage()
+                .persistent()
+                .set(&DataKey::Escrow(payment_token.clone()), &totals);
+            Self::reverse_conversion(e, &ticket.tier_symbol, token_id, &payment_token, amount);
+        }
+
+        // Invalidate and Burn (`owner` already authorized this call, so go
+        // through `update` rather than `Base::burn` which re-requires auth,
+        // and emit its `burn` event ourselves)
+        e.storage().persistent().remove(&DataKey::Listing(token_id));
+        e.storage().persistent().remove(&DataKey::Seat(token_id));
+        ticket.is_valid = false;
+        e.storage()
+            .persistent()
+            .set(&DataKey::Ticket(token_id), &ticket);
+        Base::update(e, Some(&owner), None, token_id);
+        emit_burn(e, &owner, token_id);
+        enumeration::remove(e, &TicketIndex::Owner(owner.clone()), token_id);
+        enumeration::remove(e, &TicketIndex::Tier(ticket.tier_symbol.clone()), token_id);
+        let supply = Self::total_supply(e);
+        e.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(supply - 1));
+
+        // The seat goes back on sale, or to the head of the waitlist
+        let key = DataKey::Tier(ticket.tier_symbol.clone());
+        let mut tier: Tier = e.storage().persistent().get(&key).unwrap();
+        Self::release_seat(e, &ticket.tier_symbol, &mut tier);
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Amount the holder of `token_id` would get back from `refund` right now,
+    /// in the token the ticket was paid with (0 wherever `refund` would
+    /// reject it).
+    pub fn preview_refund(e: &Env, token_id: u32) -> i128 {
+        let ticket: Ticket = match e.storage().persistent().get(&DataKey::Ticket(token_id)) {
+            Some(ticket) => ticket,
+            None => return 0,
+        };
+        if !ticket.is_valid {
+            return 0;
+        }
+        let refund_bps = match Self::refund_bps(e, &ticket.tier_symbol) {
+            Some(bps) => bps,
+            None => return 0,
+        };
+        if !Self::is_cancelled(e) && e.storage().persistent().has(&DataKey::CheckIn(token_id)) {
+            return 0;
+        }
+        ticket.price_paid * refund_bps as i128 / 10000
+    }
+
+    // Share of `price_paid` (in bps) refundable right now, `None` when closed.
+    //
+    // Cancellation and a reschedule grace window always refund in full.
+    // Otherwise refunds end at `refund_cutoff_time`; before that the tier's
+    // schedule (or the event-wide one) picks the first step whose deadline
+    // has not passed, and no schedule at all means a full refund.
+    fn refund_bps(e: &Env, tier_symbol: &Symbol) -> Option<u32> {
+        if Self::is_cancelled(e) {
+            return Some(10000);
+        }
+
+        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        let now = e.ledger().timestamp();
+        if now < event_info.refund_grace_until {
+            return Some(10000);
+        }
+        if now > event_info.refund_cutoff_time {
+            return None;
+        }
+
+        let schedule: Vec<RefundStep> = e
+            .storage()
+            .persistent()
+            .get(&DataKey::TierRefundSchedule(tier_symbol.clone()))
+            .unwrap_or(event_info.refund_schedule);
+        if schedule.is_empty() {
+            return Some(10000);
+        }
+        for step in schedule.iter() {
+            if now.saturating_add(step.seconds_before_start) <= event_info.start_time {
+                return match step.refund_bps {
+                    0 => None,
+                    bps => Some(bps),
+                };
+            }
+        }
+        None
+    }
+
+    /// Admin-only: set the event-wide refund schedule.
+    ///
+    /// Steps are ordered from furthest to closest to the start, e.g.
+    /// `[(30 days, 10000), (7 days, 5000)]` refunds 100% until 30 days out,
+    /// 50% until 7 days out and nothing after.  An empty schedule restores the
+    /// full refund up to `refund_cutoff_time`.
+    pub fn set_refund_schedule(e: &Env, schedule: Vec<RefundStep>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        Self::validate_refund_schedule(&schedule);
+
+        let mut event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        event_info.refund_schedule = schedule;
+        e.storage().instance().set(&DataKey::EventInfo, &event_info);
+    }
+
+    /// Admin-only: override the refund schedule for one tier.  An empty
+    /// schedule removes the override so the event-wide schedule applies.
+    pub fn set_tier_refund_schedule(e: &Env, tier_symbol: Symbol, schedule: Vec<RefundStep>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        Self::validate_refund_schedule(&schedule);
+
+        if !e
+            .storage()
+            .persistent()
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
+        }
+        let key = DataKey::TierRefundSchedule(tier_symbol);
+        if schedule.is_empty() {
+            e.storage().persistent().remove(&key);
+        } else {
+            e.storage().persistent().set(&key, &schedule);
+        }
+    }
+
+    fn validate_refund_schedule(schedule: &Vec<RefundStep>) {
+        let mut previous: Option<u64> = None;
+        for step in schedule.iter() {
+            if step.refund_bps > 10000 {
+                panic!("Refund share exceeds 100%");
+            }
+            if let Some(previous) = previous {
+                if step.seconds_before_start >= previous {
+                    panic!("Refund steps must be ordered furthest first");
+                }
+            }
+            previous = Some(step.seconds_before_start);
+        }
+    }
+
+    /// Admin-only: move the event to `new_start` with a new refund cutoff.
+    ///
+    /// Holders who cannot attend the new date get a mandatory grace window of
+    /// `RESCHEDULE_REFUND_GRACE_SECONDS` (capped at the new start) in which
+    /// `refund` is open even if the new cutoff has already passed.  Pricing
+    /// strategies anchored on the start time (e.g. `TimeDecay`) follow the new
+    /// date automatically.
+    pub fn reschedule(e: &Env, new_start: u64, new_cutoff: u64) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+
+        let now = e.ledger().timestamp();
+        let mut event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        if now >= event_info.start_time {
+            panic!("Event already started");
+        }
+        if new_start <= now {
+            panic!("New start must be in the future");
+        }
+        if new_cutoff > new_start {
+            panic!("Refund cutoff must not be after the start");
+        }
+
+        let old_start = event_info.start_time;
+        event_info.start_time = new_start;
+        event_info.refund_cutoff_time = new_cutoff;
+        event_info.refund_grace_until = (now + RESCHEDULE_REFUND_GRACE_SECONDS).min(new_start);
+        e.storage().instance().set(&DataKey::EventInfo, &event_info);
+
+        Rescheduled {
+            old_start,
+            new_start,
+            new_cutoff,
+        }
+        .publish(e);
+    }
+
+    pub fn get_event_info(e: &Env) -> EventInfo {
+        e.storage().instance().get(&DataKey::EventInfo).unwrap()
+    }
+
+    /// Admin-only: cancel the event.
+    ///
+    /// Sales and minting stop immediately and every holder can `refund` the
+    /// full `price_paid` from escrow regardless of `refund_cutoff_time`.  Not
+    /// possible once proceeds have been withdrawn, as escrow would no longer
+    /// cover every refund.
+    pub fn cancel_event(e: &Env, reason: String) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if Self::is_cancelled(e) {
+            panic!("Event already cancelled");
+        }
+        if e.storage().instance().has(&DataKey::ProceedsWithdrawn) {
+            panic!("Proceeds already withdrawn");
+        }
+
+        let cancellation = Cancellation {
+            reason: reason.clone(),
+            cancelled_at: e.ledger().timestamp(),
+        };
+        e.storage()
+            .instance()
+            .set(&DataKey::Cancellation, &cancellation);
+
+        Cancelled { reason }.publish(e);
+    }
+
+    pub fn get_cancellation(e: &Env) -> Option<Cancellation> {
+        e.storage().instance().get(&DataKey::Cancellation)
+    }
+
+    fn is_cancelled(e: &Env) -> bool {
+        e.storage().instance().has(&DataKey::Cancellation)
+    }
+
+    /// Running escrow totals for sales settled in `payment_token`.
+    pub fn get_escrow_totals(e: &Env, payment_token: Address) -> EscrowTotals {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Escrow(payment_token))
+            .unwrap_or(EscrowTotals {
+                collected: 0,
+                refunded: 0,
+                retained: 0,
+                withdrawn: 0,
+            })
+    }
+
+    /// Amount of `payment_token` still held in escrow for ticket holders.
+    pub fn outstanding(e: &Env, payment_token: Address) -> i128 {
+        let totals = Self::get_escrow_totals(e, payment_token);
+        totals.collected - totals.refunded - totals.withdrawn
+    }
+
+    /// Admin-only: release escrowed `payment_token` proceeds, less the
+    /// `PlatformFee` and split according to the configured `RevenueSplit`
+    /// (the organizer receives the rest).
+    ///
+    /// Sale proceeds stay locked until the event has started so that refunds
+    /// and a possible cancellation are always fully covered; see `withdrawable`.
+    pub fn withdraw_proceeds(e: &Env, payment_token: Address) -> i128 {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let amount = Self::withdrawable(e, payment_token.clone());
+        if amount <= 0 {
+            if Self::is_cancelled(e) {
+                panic!("Event cancelled");
+            }
+            let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+            if e.ledger().timestamp() < event_info.start_time {
+                panic!("Proceeds locked until the event starts");
+            }
+            return 0;
+        }
+
+        Self::distribute_proceeds(e, &payment_token, &admin, amount);
+
+        let mut totals = Self::get_escrow_totals(e, payment_token.clone());
+        totals.withdrawn += amount;
+        let settled = totals.withdrawn > totals.retained;
+        e.storage()
+            .persistent()
+            .set(&DataKey::Escrow(payment_token), &totals);
+        if settled {
+            // Sale proceeds (not just refund remainders) have left escrow
+            e.storage()
+                .instance()
+                .set(&DataKey::ProceedsWithdrawn, &true);
+        }
+        amount
+    }
+
+    /// Admin-only: configure the co-organizer payees.
+    ///
+    /// Shares are applied to net proceeds when they are withdrawn, so anything
+    /// refunded before that is taken out of every party's share in proportion.
+    pub fn set_revenue_split(e: &Env, split: RevenueSplit) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let fee_bps = Self::get_platform_fee(e).map_or(0, |fee| fee.fee_bps);
+        if fee_bps + Self::payee_bps(&split) > 10000 {
+            panic!("Revenue shares exceed 100%");
+        }
+        e.storage().instance().set(&DataKey::RevenueSplit, &split);
+    }
+
+    pub fn get_revenue_split(e: &Env) -> Option<RevenueSplit> {
+        e.storage().instance().get(&DataKey::RevenueSplit)
+    }
+
+    // Total share of the co-organizer payees, in bps
+    fn payee_bps(split: &RevenueSplit) -> u32 {
+        let mut total_bps = 0;
+        for payee in split.payees.iter() {
+            total_bps += payee.share_bps;
+        }
+        total_bps
+    }
+
+    /// Platform-only: set the fee taken from every withdrawal and the
+    /// treasury it is paid to.
+    pub fn set_platform_fee(e: &Env, fee: PlatformFee) {
+        let platform = Self::get_platform(e);
+        platform.require_auth();
+
+        let payee_bps = Self::get_revenue_split(e).map_or(0, |split| Self::payee_bps(&split));
+        if fee.fee_bps + payee_bps > 10000 {
+            panic!("Revenue shares exceed 100%");
+        }
+        e.storage().instance().set(&DataKey::PlatformFee, &fee);
+    }
+
+    pub fn get_platform_fee(e: &Env) -> Option<PlatformFee> {
+        e.storage().instance().get(&DataKey::PlatformFee)
+    }
+
+    /// Authority over the platform fee, fixed at `initialize`.
+    pub fn get_platform(e: &Env) -> Address {
+        e.storage().instance().get(&DataKey::Platform).unwrap()
+    }
+
+    // Pay `amount` out of escrow: platform fee and payee shares first, the
+    // organizer gets the remainder (including rounding dust).
+    fn distribute_proceeds(e: &Env, payment_token: &Address, organizer: &Address, amount: i128) {
+        let token_client = token::Client::new(e, payment_token);
+        let escrow = e.current_contract_address();
+        let mut remaining = amount;
+
+        if let Some(platform_fee) = Self::get_platform_fee(e) {
+            let fee = amount * platform_fee.fee_bps as i128 / 10000;
+            if fee > 0 {
+                token_client.transfer(&escrow, &platform_fee.treasury, &fee);
+                remaining -= fee;
+            }
+        }
+        if let Some(split) = Self::get_revenue_split(e) {
+            for payee in split.payees.iter() {
+                let share = amount * payee.share_bps as i128 / 10000;
+                if share > 0 {
+                    token_client.transfer(&escrow, &payee.address, &share);
+                    remaining -= share;
+                }
+            }
+        }
+
+        if remaining > 0 {
+            token_client.transfer(&escrow, organizer, &remaining);
+        }
+    }
+
+    /// Amount of `payment_token` the organizer can withdraw right now.
+    ///
+    /// Before the event starts (or after a cancellation) only the remainders
+    /// kept from partial refunds are free; once the event has started all
+    /// escrowed sales can be withdrawn.
+    pub fn withdrawable(e: &Env, payment_token: Address) -> i128 {
+        let totals = Self::get_escrow_totals(e, payment_token);
+        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        if !Self::is_cancelled(e) && e.ledger().timestamp() >= event_info.start_time {
+            totals.collected - totals.refunded - totals.withdrawn
+        } else {
+            totals.retained - totals.withdrawn
+        }
+    }
 
-        // Process payment
+    /// Admin-only: configure the resale marketplace.
+    ///
+    /// `max_price_bps` caps listings relative to the ticket's `price_paid`
+    /// (11000 = 110%) and `royalty_bps` of every resale goes to the organizer.
+    pub fn set_resale_config(e: &Env, config: ResaleConfig) {
         let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        let token_client = token::Client::new(e, &payment_token);
-        token_client.transfer(&buyer, &admin, &price);
+        admin.require_auth();
+
+        if config.royalty_bps > 10000 {
+            panic!("Royalty exceeds 100%");
+        }
+        e.storage().instance().set(&DataKey::ResaleConfig, &config);
+    }
+
+    pub fn get_resale_config(e: &Env) -> Option<ResaleConfig> {
+        e.storage().instance().get(&DataKey::ResaleConfig)
+    }
+
+    // Resale config, panicking unless the organizer has enabled resale
+    fn resale_config(e: &Env) -> ResaleConfig {
+        match Self::get_resale_config(e) {
+            Some(config) if config.enabled => config,
+            _ => panic!("Resale is disabled"),
+        }
+    }
+
+    /// List `token_id` for resale at `price`, settled in the token the ticket
+    /// was originally bought with.
+    pub fn list_for_resale(e: &Env, seller: Address, token_id: u32, price: i128) {
+        seller.require_auth();
+
+        let config = Self::resale_config(e);
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+        if Self::owner_of(e, token_id) != seller {
+            panic!("Not the ticket owner");
+        }
 
-        // Mint Token
-        let mut counter: u32 = e
+        let ticket: Ticket = e
             .storage()
-            .instance()
-            .get(&DataKey::TokenIdCounter)
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
             .unwrap();
-        counter += 1;
-        let token_id = counter;
-        e.storage()
-            .instance()
-            .set(&DataKey::TokenIdCounter, &counter);
-
-        Base::sequential_mint(e, &buyer);
+        if !ticket.is_valid {
+            panic!("Ticket is not valid");
+        }
+        if ticket.payment_token.is_none() {
+            panic!("Complimentary tickets cannot be resold");
+        }
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(ticket.tier_symbol))
+            .unwrap();
+        if tier.transfer_policy == TransferPolicy::Soulbound {
+            panic!("Soulbound: Tickets cannot be transferred");
+        }
+        if e.storage().persistent().has(&DataKey::CheckIn(token_id)) {
+            panic!("Ticket already used");
+        }
+        if price < 0 || price > ticket.price_paid * config.max_price_bps as i128 / 10000 {
+            panic!("Price exceeds resale cap");
+        }
 
-        let ticket = Ticket {
-            tier_symbol: tier_symbol.clone(),
-            purchase_time: e.ledger().timestamp(),
-            price_paid: price,
-            is_valid: true,
-        };
+        let listing = Listing { seller, price };
         e.storage()
             .persistent()
-            .set(&DataKey::Ticket(token_id), &ticket);
+            .set(&DataKey::Listing(token_id), &listing);
+    }
 
-        tier.minted += 1;
-        tier.current_price = price; // Update the current recorded price for this tier
-        e.storage().persistent().set(&key, &tier);
+    pub fn cancel_listing(e: &Env, seller: Address, token_id: u32) {
+        seller.require_auth();
 
-        // Update pricing config last update time
-        let mut config: PricingConfig =
-            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
-        config.last_update_time = e.ledger().timestamp();
-        e.storage().instance().set(&DataKey::PricingConfig, &config);
+        let key = DataKey::Listing(token_id);
+        let listing: Listing = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Ticket not listed"));
+        if listing.seller != seller {
+            panic!("Not the seller");
+        }
+        e.storage().persistent().remove(&key);
     }
 
-    // Refund a ticket
-    pub fn refund(e: &Env, owner: Address, payment_token: Address, token_id: u32) {
-        owner.require_auth();
+    pub fn get_listing(e: &Env, token_id: u32) -> Option<Listing> {
+        e.storage().persistent().get(&DataKey::Listing(token_id))
+    }
 
-        let current_owner = Self::owner_of(e, token_id);
-        if owner != current_owner {
-            panic!("Not the ticket owner");
+    /// Buy a listed ticket.  The buyer pays the listing price, the organizer
+    /// receives the resale royalty and the seller the rest.  The ticket keeps
+    /// its original `price_paid`, which is what escrow refunds are based on.
+    pub fn buy_resale(e: &Env, buyer: Address, token_id: u32, max_price: i128) {
+        buyer.require_auth();
+
+        let config = Self::resale_config(e);
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
         }
 
-        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
-        if e.ledger().timestamp() > event_info.refund_cutoff_time {
-            panic!("Refund window closed");
+        let key = DataKey::Listing(token_id);
+        let listing: Listing = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Ticket not listed"));
+        if listing.price > max_price {
+            panic!("Price exceeds max_price");
+        }
+        if listing.seller == buyer {
+            panic!("Cannot buy your own listing");
         }
+        if Self::owner_of(e, token_id) != listing.seller {
+            panic!("Listing is stale");
+        }
+        Self::check_transfer_policy(e, &buyer, token_id);
 
-        let mut ticket: Ticket = e
+        let ticket: Ticket = e
             .storage()
             .persistent()
             .get(&DataKey::Ticket(token_id))
             .unwrap();
-        if !ticket.is_valid {
-            panic!("Ticket already invalidated");
-        }
+        let payment_token = ticket.payment_token.unwrap();
 
-        // Process refund
+        // Settle: royalty to the organizer, the rest to the seller
         let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
         let token_client = token::Client::new(e, &payment_token);
-        token_client.transfer(&admin, &owner, &ticket.price_paid);
+        let royalty = listing.price * config.royalty_bps as i128 / 10000;
+        if royalty > 0 {
+            token_client.transfer(&buyer, &admin, &royalty);
+        }
+        if listing.price - royalty > 0 {
+            token_client.transfer(&buyer, &listing.seller, &(listing.price - royalty));
+        }
 
-        // Invalidate and Burn
-        ticket.is_valid = false;
+        e.storage().persistent().remove(&key);
+        Self::move_ticket(e, &listing.seller, &buyer, token_id);
+
+        Resold {
+            token_id,
+            seller: listing.seller,
+            buyer,
+            price: listing.price,
+        }
+        .publish(e);
+    }
+
+    /// Admin-only: set how tickets of `tier_symbol` may change hands, by
+    /// transfer or through the resale marketplace.
+    pub fn set_transfer_policy(e: &Env, tier_symbol: Symbol, policy: TransferPolicy) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Tier(tier_symbol);
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.transfer_policy = policy;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Admin-only: approve a single transfer of `token_id` to `to` for tiers
+    /// using `TransferPolicy::OrganizerApproved`.  The approval is consumed by
+    /// the transfer.
+    pub fn approve_transfer(e: &Env, token_id: u32, to: Address) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if !Self::validate_ticket(e, token_id) {
+            panic!("Ticket is not valid");
+        }
         e.storage()
             .persistent()
-            .set(&DataKey::Ticket(token_id), &ticket);
-        Base::burn(e, &owner, token_id);
+            .set(&DataKey::TransferApproval(token_id), &to);
+    }
+
+    // Enforce the tier's transfer policy for moving `token_id` to `to`
+    fn check_transfer_policy(e: &Env, to: &Address, token_id: u32) {
+        let ticket: Ticket = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
+            .unwrap_or_else(|| panic!("Ticket not found"));
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(ticket.tier_symbol))
+            .unwrap();
+
+        match tier.transfer_policy {
+            TransferPolicy::Soulbound => panic!("Soulbound: Tickets cannot be transferred"),
+            TransferPolicy::TransferableUntil(cutoff) => {
+                if e.ledger().timestamp() > cutoff {
+                    panic!("Transfer window closed");
+                }
+            }
+            TransferPolicy::OrganizerApproved => {
+                let key = DataKey::TransferApproval(token_id);
+                let approved: Option<Address> = e.storage().persistent().get(&key);
+                if approved.as_ref() != Some(to) {
+                    panic!("Transfer not approved by organizer");
+                }
+                e.storage().persistent().remove(&key);
+            }
+        }
+
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+        if !ticket.is_valid {
+            panic!("Ticket is not valid");
+        }
+        if e.storage().persistent().has(&DataKey::CheckIn(token_id)) {
+            panic!("Ticket already used");
+        }
+    }
+
+    // Move ownership of a ticket without re-requiring the holder's auth;
+    // callers are responsible for authorization and policy checks.
+    fn move_ticket(e: &Env, from: &Address, to: &Address, token_id: u32) {
+        e.storage().persistent().remove(&DataKey::Listing(token_id));
+        Base::update(e, Some(from), Some(to), token_id);
+        emit_transfer(e, from, to, token_id);
+        enumeration::remove(e, &TicketIndex::Owner(from.clone()), token_id);
+        enumeration::add(e, &TicketIndex::Owner(to.clone()), token_id);
+    }
+
+    /// Tickets currently in circulation (minted and not burned by a refund).
+    pub fn total_supply(e: &Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0)
+    }
+
+    /// Token IDs held by `owner`, `limit` (at most `MAX_PAGE_SIZE`) at a time
+    /// from position `start`.  Positions shift as tickets are moved or
+    /// refunded; `balance` gives the total.
+    pub fn tickets_of(e: &Env, owner: Address, start: u32, limit: u32) -> Vec<u32> {
+        enumeration::page(
+            e,
+            &TicketIndex::Owner(owner),
+            start,
+            limit.min(MAX_PAGE_SIZE),
+        )
+    }
+
+    /// Token IDs of `tier_symbol` in circulation, paged like `tickets_of`.
+    pub fn tickets_by_tier(e: &Env, tier_symbol: Symbol, start: u32, limit: u32) -> Vec<u32> {
+        enumeration::page(
+            e,
+            &TicketIndex::Tier(tier_symbol),
+            start,
+            limit.min(MAX_PAGE_SIZE),
+        )
     }
 
     // Ticket Validation
@@ -365,6 +3240,300 @@ impl SoulboundTicketContract {
         ticket.is_valid
     }
 
+    /// Admin-only: allow `scanner` to check tickets in at the gate.
+    pub fn add_scanner(e: &Env, scanner: Address) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        e.storage()
+            .persistent()
+            .set(&DataKey::Scanner(scanner), &true);
+    }
+
+    /// Admin-only: revoke a scanner address.
+    pub fn remove_scanner(e: &Env, scanner: Address) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        e.storage().persistent().remove(&DataKey::Scanner(scanner));
+    }
+
+    pub fn is_scanner(e: &Env, scanner: Address) -> bool {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Scanner(scanner))
+            .unwrap_or(false)
+    }
+
+    /// Admin-only: set how many times a ticket of `tier_symbol` may be scanned in.
+    pub fn set_entry_policy(e: &Env, tier_symbol: Symbol, policy: EntryPolicy) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if policy == EntryPolicy::Limited(0) {
+            panic!("Entry limit must be positive");
+        }
+
+        let key = DataKey::Tier(tier_symbol);
+        let mut tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.entry_policy = policy;
+        e.storage().persistent().set(&key, &tier);
+    }
+
+    /// Record an entry for `token_id` at the gate.
+    ///
+    /// `scanner` must be registered via `add_scanner`.  The first scan counts
+    /// the ticket towards its tier's `checked_in_count`; further scans are
+    /// accepted or rejected according to the tier's `EntryPolicy`.
+    pub fn check_in(e: &Env, scanner: Address, token_id: u32) {
+        scanner.require_auth();
+        if !Self::is_scanner(e, scanner.clone()) {
+            panic!("Not an authorized scanner");
+        }
+        if Self::is_cancelled(e) {
+            panic!("Event cancelled");
+        }
+
+        let ticket: Ticket = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
+            .unwrap_or_else(|| panic!("Ticket not found"));
+        if !ticket.is_valid {
+            panic!("Ticket is not valid");
+        }
+
+        let tier_key = DataKey::Tier(ticket.tier_symbol);
+        let mut tier: Tier = e.storage().persistent().get(&tier_key).unwrap();
+
+        let now = e.ledger().timestamp();
+        let key = DataKey::CheckIn(token_id);
+        let record = match e.storage().persistent().get::<_, CheckInRecord>(&key) {
+            Some(mut record) => {
+                match tier.entry_policy {
+                    EntryPolicy::SingleEntry => panic!("Ticket already checked in"),
+                    EntryPolicy::Limited(max_entries) if record.entries >= max_entries => {
+                        panic!("Entry limit reached")
+                    }
+                    _ => {}
+                }
+                record.entries += 1;
+                record.last_entry_time = now;
+                record.last_scanner = scanner;
+                record
+            }
+            None => {
+                tier.checked_in += 1;
+                e.storage().persistent().set(&tier_key, &tier);
+                CheckInRecord {
+                    first_entry_time: now,
+                    last_entry_time: now,
+                    last_scanner: scanner,
+                    entries: 1,
+                }
+            }
+        };
+        e.storage().persistent().set(&key, &record);
+    }
+
+    pub fn get_check_in(e: &Env, token_id: u32) -> Option<CheckInRecord> {
+        e.storage().persistent().get(&DataKey::CheckIn(token_id))
+    }
+
+    /// Number of distinct tickets of `tier_symbol` that have entered the venue.
+    pub fn checked_in_count(e: &Env, tier_symbol: Symbol) -> u32 {
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(tier_symbol))
+            .unwrap_or_else(|| panic!("Tier not found"));
+        tier.checked_in
+    }
+
+    /// Admin-only: image wallets show for tickets of `tier_symbol` (None
+    /// clears it).
+    pub fn set_tier_image(e: &Env, tier_symbol: Symbol, image_uri: Option<String>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if !e
+            .storage()
+            .persistent()
+            .has(&DataKey::Tier(tier_symbol.clone()))
+        {
+            panic!("Tier not found");
+        }
+        let key = DataKey::TierImage(tier_symbol);
+        match image_uri {
+            Some(image_uri) => {
+                if image_uri.len() > MAX_IMAGE_URI_LEN {
+                    panic!("Image URI too long");
+                }
+                e.storage().persistent().set(&key, &image_uri);
+            }
+            None => e.storage().persistent().remove(&key),
+        }
+    }
+
+    pub fn get_tier_image(e: &Env, tier_symbol: Symbol) -> Option<String> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::TierImage(tier_symbol))
+    }
+
+    /// Admin-only: label `token_id` with its seat (e.g. "B-14"), or clear it
+    /// with None.
+    pub fn assign_seat(e: &Env, token_id: u32, seat: Option<String>) {
+        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if !Self::validate_ticket(e, token_id) {
+            panic!("Ticket is not valid");
+        }
+        let key = DataKey::Seat(token_id);
+        match seat {
+            Some(seat) => {
+                if seat.is_empty() || seat.len() > MAX_SEAT_LEN {
+                    panic!("Invalid seat label");
+                }
+                e.storage().persistent().set(&key, &seat);
+            }
+            None => e.storage().persistent().remove(&key),
+        }
+    }
+
+    pub fn get_seat(e: &Env, token_id: u32) -> Option<String> {
+        e.storage().persistent().get(&DataKey::Seat(token_id))
+    }
+
+    /// JSON metadata for `token_id`: tier, seat, check-in status and
+    /// validity.  `token_uri` serves the same document as a base64 data URI.
+    pub fn token_metadata(e: &Env, token_id: u32) -> String {
+        let ticket: Ticket = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
+            .unwrap_or_else(|| panic!("Ticket not found"));
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(ticket.tier_symbol.clone()))
+            .unwrap();
+
+        // Refunded tickets are burned, so `Base::token_uri` would reject them;
+        // their record still describes what was sold
+        let base_uri = if ticket.is_valid {
+            Base::token_uri(e, token_id)
+        } else {
+            String::from_str(e, "")
+        };
+        let status: &'static [u8] = if !ticket.is_valid {
+            b"Invalid"
+        } else if Self::is_cancelled(e) {
+            b"Cancelled"
+        } else {
+            b"Valid"
+        };
+
+        metadata::ticket_json(
+            e,
+            &TicketMetadata {
+                collection: Base::name(e),
+                token_id,
+                tier_name: tier.name,
+                image: Self::get_tier_image(e, ticket.tier_symbol),
+                external_url: if base_uri.is_empty() {
+                    None
+                } else {
+                    Some(base_uri)
+                },
+                seat: Self::get_seat(e, token_id),
+                checked_in: e.storage().persistent().has(&DataKey::CheckIn(token_id)),
+                status,
+            },
+        )
+    }
+
+    /// Register the ed25519 public key `owner` will use to sign gate
+    /// presentations (e.g. a QR code generated by their wallet app).
+    pub fn register_presentation_key(e: &Env, owner: Address, public_key: BytesN<32>) {
+        owner.require_auth();
+        e.storage()
+            .persistent()
+            .set(&DataKey::PresentationKey(owner), &public_key);
+    }
+
+    pub fn get_presentation_key(e: &Env, owner: Address) -> Option<BytesN<32>> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::PresentationKey(owner))
+    }
+
+    /// Exact bytes a holder must sign to present `token_id` at the gate.
+    ///
+    /// Layout: `PRESENTATION_DOMAIN || contract address (XDR) || token_id ||
+    /// nonce || expiry`, integers big-endian.  Binding the contract address
+    /// stops a proof for one event being replayed against another.
+    pub fn presentation_challenge(e: &Env, token_id: u32, nonce: u64, expiry: u64) -> Bytes {
+        let mut message = Bytes::from_slice(e, PRESENTATION_DOMAIN);
+        message.append(&e.current_contract_address().to_xdr(e));
+        message.extend_from_array(&token_id.to_be_bytes());
+        message.extend_from_array(&nonce.to_be_bytes());
+        message.extend_from_array(&expiry.to_be_bytes());
+        message
+    }
+
+    /// Verify that whoever produced `signature` controls the wallet holding
+    /// `token_id`.
+    ///
+    /// The signature must come from the holder's registered presentation key
+    /// over `presentation_challenge(token_id, nonce, expiry)`.  Challenges are
+    /// short-lived (`expiry` at most `MAX_PRESENTATION_WINDOW_SECONDS` ahead)
+    /// and each nonce is consumed, so a screenshot of a QR code cannot be
+    /// replayed.  Returns the verified holder.
+    pub fn verify_presentation(
+        e: &Env,
+        token_id: u32,
+        nonce: u64,
+        expiry: u64,
+        signature: BytesN<64>,
+    ) -> Address {
+        let now = e.ledger().timestamp();
+        if now > expiry {
+            panic!("Presentation expired");
+        }
+        if expiry > now + MAX_PRESENTATION_WINDOW_SECONDS {
+            panic!("Presentation expiry too far in the future");
+        }
+
+        if !Self::validate_ticket(e, token_id) {
+            panic!("Ticket is not valid");
+        }
+        let holder = Self::owner_of(e, token_id);
+        let public_key = Self::get_presentation_key(e, holder.clone())
+            .unwrap_or_else(|| panic!("No presentation key registered"));
+
+        let nonce_key = DataKey::PresentationNonce(token_id, nonce);
+        if e.storage().temporary().has(&nonce_key) {
+            panic!("Presentation already used");
+        }
+
+        let message = Self::presentation_challenge(e, token_id, nonce, expiry);
+        e.crypto().ed25519_verify(&public_key, &message, &signature);
+
+        // Remember the nonce for at least as long as the challenge is valid
+        e.storage().temporary().set(&nonce_key, &true);
+        e.storage().temporary().extend_ttl(
+            &nonce_key,
+            PRESENTATION_NONCE_TTL_LEDGERS,
+            PRESENTATION_NONCE_TTL_LEDGERS,
+        );
+
+        holder
+    }
+
     // View functions logic
     pub fn get_ticket(e: &Env, token_id: u32) -> Ticket {
         e.storage()
@@ -387,31 +3556,51 @@ impl NonFungibleToken for SoulboundTicketContract {
         Self::ContractType::owner_of(e, token_id)
     }
 
-    // Soulbound restrictions overrides
-    fn transfer(_e: &Env, _from: Address, _to: Address, _token_id: u32) {
-        panic!("Soulbound: Tickets cannot be transferred");
+    // Transfers follow the ticket tier's `TransferPolicy` (soulbound by default)
+    fn transfer(e: &Env, from: Address, to: Address, token_id: u32) {
+        from.require_auth();
+        Self::check_transfer_policy(e, &to, token_id);
+        Self::move_ticket(e, &from, &to, token_id);
     }
 
-    fn transfer_from(_e: &Env, _spender: Address, _from: Address, _to: Address, _token_id: u32) {
-        panic!("Soulbound: Tickets cannot be transferred");
+    fn transfer_from(e: &Env, spender: Address, from: Address, to: Address, token_id: u32) {
+        spender.require_auth();
+        if spender != from && Self::ContractType::get_approved(e, token_id) != Some(spender) {
+            panic!("Not approved for transfer");
+        }
+        Self::check_transfer_policy(e, &to, token_id);
+        Self::move_ticket(e, &from, &to, token_id);
     }
 
     fn approve(
-        _e: &Env,
-        _approver: Address,
-        _approved: Address,
-        _token_id: u32,
-        _live_until_ledger: u32,
+        e: &Env,
+        approver: Address,
+        approved: Address,
+        token_id: u32,
+        live_until_ledger: u32,
     ) {
-        panic!("Soulbound: Approval disabled for non-transferable tokens");
+        let ticket: Ticket = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(token_id))
+            .unwrap_or_else(|| panic!("Ticket not found"));
+        let tier: Tier = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Tier(ticket.tier_symbol))
+            .unwrap();
+        if tier.transfer_policy == TransferPolicy::Soulbound {
+            panic!("Soulbound: Approval disabled for non-transferable tokens");
+        }
+        Self::ContractType::approve(e, &approver, &approved, token_id, live_until_ledger);
     }
 
     fn approve_for_all(_e: &Env, _owner: Address, _operator: Address, _live_until_ledger: u32) {
         panic!("Soulbound: Approval disabled for non-transferable tokens");
     }
 
-    fn get_approved(_e: &Env, _token_id: u32) -> Option<Address> {
-        None
+    fn get_approved(e: &Env, token_id: u32) -> Option<Address> {
+        Self::ContractType::get_approved(e, token_id)
     }
 
     fn is_approved_for_all(_e: &Env, _owner: Address, _operator: Address) -> bool {
@@ -428,7 +3617,7 @@ impl NonFungibleToken for SoulboundTicketContract {
     }
 
     fn token_uri(e: &Env, token_id: u32) -> String {
-        Self::ContractType::token_uri(e, token_id)
+        metadata::data_uri(e, &Self::token_metadata(e, token_id))
     }
 }
 
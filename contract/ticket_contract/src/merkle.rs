@@ -0,0 +1,41 @@
+/// merkle.rs — Allowlist proofs for presale phases
+///
+/// Eligible buyers are committed to as a Merkle tree whose root is stored per
+/// tier, so thousands of addresses cost a single 32-byte entry on-chain.
+///
+/// Leaf:  sha256(buyer address XDR || allocation as u32 big-endian)
+/// Node:  sha256(min(a, b) || max(a, b))
+///
+/// Sorting each pair before hashing (as OpenZeppelin's `MerkleProof` does)
+/// means proofs carry no left/right flags.
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+/// Hash an allowlist entry.  `allocation` is the number of presale tickets
+/// the address may buy (0 = no per-address limit beyond the wallet cap).
+pub fn leaf_hash(e: &Env, buyer: &Address, allocation: u32) -> BytesN<32> {
+    let mut data = Bytes::new(e);
+    data.append(&buyer.clone().to_xdr(e));
+    data.extend_from_array(&allocation.to_be_bytes());
+    e.crypto().sha256(&data).to_bytes()
+}
+
+/// Hash two sibling nodes in sorted order.
+pub fn hash_pair(e: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a.to_array() <= b.to_array() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut data = Bytes::from_array(e, &first.to_array());
+    data.extend_from_array(&second.to_array());
+    e.crypto().sha256(&data).to_bytes()
+}
+
+/// Fold `proof` over `leaf` and compare against `root`.
+pub fn verify(e: &Env, root: &BytesN<32>, proof: &Vec<BytesN<32>>, leaf: BytesN<32>) -> bool {
+    let mut computed = leaf;
+    for sibling in proof.iter() {
+        computed = hash_pair(e, &computed, &sibling);
+    }
+    computed == *root
+}
@@ -0,0 +1,188 @@
+/// metadata.rs — On-chain JSON metadata for `token_uri`
+///
+/// The contract has no allocator, so documents are assembled in a fixed
+/// stack buffer and served as a base64 `data:` URI.  Wallets can then show
+/// each ticket's tier, seat and status without an off-chain metadata server.
+use soroban_sdk::{Env, String};
+use stellar_tokens::non_fungible::{MAX_BASE_URI_LEN, MAX_NAME_LEN, MAX_NUM_DIGITS};
+
+use crate::{MAX_IMAGE_URI_LEN, MAX_SEAT_LEN, MAX_TIER_NAME_LEN};
+
+// Longest variable text a document can hold: collection name, tier name,
+// image, external URL (base URI + token ID) and seat
+const MAX_FIELDS_LEN: usize = MAX_NAME_LEN
+    + MAX_TIER_NAME_LEN as usize
+    + MAX_IMAGE_URI_LEN as usize
+    + MAX_BASE_URI_LEN
+    + MAX_NUM_DIGITS
+    + MAX_SEAT_LEN as usize;
+// Every byte may be escaped as `\u00XX`; the keys and fixed values fit in 256
+const MAX_METADATA_LEN: usize = MAX_FIELDS_LEN * 6 + 256;
+const DATA_URI_PREFIX: &[u8] = b"data:application/json;base64,";
+const MAX_URI_LEN: usize = DATA_URI_PREFIX.len() + MAX_METADATA_LEN.div_ceil(3) * 4;
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// What `token_uri` reports for one ticket.
+pub struct TicketMetadata {
+    pub collection: String,
+    pub token_id: u32,
+    pub tier_name: String,
+    pub image: Option<String>,
+    /// Off-chain page for the ticket (the collection base URI + ID), if any.
+    pub external_url: Option<String>,
+    pub seat: Option<String>,
+    pub checked_in: bool,
+    pub status: &'static [u8],
+}
+
+struct JsonWriter {
+    buf: [u8; MAX_METADATA_LEN],
+    len: usize,
+}
+
+impl JsonWriter {
+    fn new() -> Self {
+        JsonWriter {
+            buf: [0u8; MAX_METADATA_LEN],
+            len: 0,
+        }
+    }
+
+    fn raw(&mut self, bytes: &[u8]) {
+        let end = self.len + bytes.len();
+        if end > MAX_METADATA_LEN {
+            panic!("Metadata too large");
+        }
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+    }
+
+    fn number(&mut self, mut n: u32) {
+        let mut digits = [0u8; 10];
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        self.raw(&digits[i..]);
+    }
+
+    /// Write `bytes` as the inside of a JSON string, escaping as needed.
+    fn escaped(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            match b {
+                b'"' => self.raw(b"\\\""),
+                b'\\' => self.raw(b"\\\\"),
+                0x00..=0x1f => {
+                    let hex = b"0123456789abcdef";
+                    self.raw(b"\\u00");
+                    self.raw(&[hex[(b >> 4) as usize], hex[(b & 0xf) as usize]]);
+                }
+                _ => self.raw(&[b]),
+            }
+        }
+    }
+
+    fn escaped_string(&mut self, value: &String) {
+        let len = value.len() as usize;
+        if len > MAX_METADATA_LEN {
+            panic!("Metadata too large");
+        }
+        let mut bytes = [0u8; MAX_METADATA_LEN];
+        value.copy_into_slice(&mut bytes[..len]);
+        self.escaped(&bytes[..len]);
+    }
+
+    fn quoted(&mut self, bytes: &[u8]) {
+        self.raw(b"\"");
+        self.escaped(bytes);
+        self.raw(b"\"");
+    }
+
+    fn string(&mut self, value: &String) {
+        self.raw(b"\"");
+        self.escaped_string(value);
+        self.raw(b"\"");
+    }
+
+    fn attribute(&mut self, trait_type: &[u8]) {
+        self.raw(b"{\"trait_type\":");
+        self.quoted(trait_type);
+        self.raw(b",\"value\":");
+    }
+}
+
+/// Render `metadata` as an ERC-721 style JSON document.
+pub fn ticket_json(e: &Env, metadata: &TicketMetadata) -> String {
+    let mut doc = JsonWriter::new();
+
+    doc.raw(b"{\"name\":\"");
+    doc.escaped_string(&metadata.collection);
+    doc.raw(b" #");
+    doc.number(metadata.token_id);
+    doc.raw(b"\"");
+
+    if let Some(image) = &metadata.image {
+        doc.raw(b",\"image\":");
+        doc.string(image);
+    }
+    if let Some(external_url) = &metadata.external_url {
+        doc.raw(b",\"external_url\":");
+        doc.string(external_url);
+    }
+
+    doc.raw(b",\"attributes\":[");
+    doc.attribute(b"Tier");
+    doc.string(&metadata.tier_name);
+    if let Some(seat) = &metadata.seat {
+        doc.raw(b"},");
+        doc.attribute(b"Seat");
+        doc.string(seat);
+    }
+    doc.raw(b"},");
+    doc.attribute(b"Checked In");
+    doc.quoted(if metadata.checked_in { b"Yes" } else { b"No" });
+    doc.raw(b"},");
+    doc.attribute(b"Status");
+    doc.quoted(metadata.status);
+    doc.raw(b"}]}");
+
+    String::from_bytes(e, &doc.buf[..doc.len])
+}
+
+/// Wrap a JSON document as a `data:application/json;base64,` URI.
+pub fn data_uri(e: &Env, json: &String) -> String {
+    let len = json.len() as usize;
+    if len > MAX_METADATA_LEN {
+        panic!("Metadata too large");
+    }
+    let mut input = [0u8; MAX_METADATA_LEN];
+    json.copy_into_slice(&mut input[..len]);
+
+    let mut uri = [0u8; MAX_URI_LEN];
+    uri[..DATA_URI_PREFIX.len()].copy_from_slice(DATA_URI_PREFIX);
+    let mut end = DATA_URI_PREFIX.len();
+    for chunk in input[..len].chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            uri[end + i] = if i <= chunk.len() {
+                BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize]
+            } else {
+                b'='
+            };
+        }
+        end += 4;
+    }
+
+    String::from_bytes(e, &uri[..end])
+}
@@ -11,7 +11,12 @@
 ///   get_value(key: String) -> (i128, u64)
 ///     - i128: price with 8 decimal places (e.g. 100_000_000 = $1.00)
 ///     - u64:  UNIX timestamp of the last price update
-use soroban_sdk::{contractclient, symbol_short, Address, Env, IntoVal, String, TryFromVal, Val};
+///
+/// SEP-40 oracles (e.g. Reflector) are supported through the same adapter
+/// layer; their prices are normalized to DIA's 8 decimals.
+use soroban_sdk::{contractclient, contracttype, Address, Env, String, Symbol, Vec};
+
+use crate::storage_types::{OracleAdapter, OracleSource};
 
 /// How long (in seconds) a price is considered fresh. Default: 24 hours.
 pub const DEFAULT_STALENESS_SECONDS: u64 = 86_400;
@@ -32,6 +37,7 @@ pub const DIA_ORACLE_DECIMALS: i128 = 100_000_000;
 /// Trait mirroring the on-chain DIA Oracle public interface.
 /// `contractclient` generates `DiaOraclePriceClient` from this.
 #[contractclient(name = "DiaOraclePriceClient")]
+#[allow(dead_code)] // Only the generated client is used
 pub trait DiaOracleInterface {
     /// Returns (price_8decimals, unix_timestamp).
     fn get_value(env: Env, pair: String) -> (i128, u64);
@@ -47,12 +53,81 @@ pub trait DiaOracleInterface {
 /// Trait mirroring a minimal DEX pool or price-router interface.
 /// `contractclient` generates `DexPriceRouterClient`.
 #[contractclient(name = "DexPriceRouterClient")]
+#[allow(dead_code)] // Only the generated client is used
 pub trait DexPriceRouterInterface {
     /// Returns the current spot price of `pair` expressed in the same
     /// 8-decimal format as DIA (100_000_000 == $1.00).
     fn get_spot_price(env: Env, pair: String) -> i128;
 }
 
+// --------------------------------------------------------------------------
+// SEP-40 oracle client
+//
+// SEP-40 feeds price an asset against the oracle's base asset:
+//   lastprice(asset: Asset) -> Option<PriceData>
+//   decimals() -> u32
+// Prices carry `decimals()` decimal places (14 for Reflector).
+// --------------------------------------------------------------------------
+
+/// SEP-40 `Asset`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Sep40Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// SEP-40 `PriceData`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sep40PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Trait mirroring the SEP-40 price feed interface.
+/// `contractclient` generates `Sep40OracleClient`.
+#[contractclient(name = "Sep40OracleClient")]
+#[allow(dead_code)] // Only the generated client is used
+pub trait Sep40OracleInterface {
+    fn lastprice(env: Env, asset: Sep40Asset) -> Option<Sep40PriceData>;
+    fn decimals(env: Env) -> u32;
+}
+
+/// Decimal places of the prices this module hands out (DIA convention).
+const PRICE_DECIMALS: u32 = 8;
+
+/// Rescale `price` from `decimals` decimal places to `PRICE_DECIMALS`.
+/// `None` on overflow.
+pub fn normalize_price(price: i128, decimals: u32) -> Option<i128> {
+    if decimals >= PRICE_DECIMALS {
+        10i128
+            .checked_pow(decimals - PRICE_DECIMALS)
+            .map(|scale| price / scale)
+    } else {
+        10i128
+            .checked_pow(PRICE_DECIMALS - decimals)
+            .and_then(|scale| price.checked_mul(scale))
+    }
+}
+
+/// SEP-40 asset for the base of a `"BASE/QUOTE"` pair (`"XLM/USD"` ->
+/// `Other("XLM")`); the quote is implied by the feed.
+fn sep40_asset(e: &Env, pair: &String) -> Option<Sep40Asset> {
+    let mut buf = [0u8; 32];
+    let len = pair.len() as usize;
+    if len > buf.len() {
+        return None;
+    }
+    pair.copy_into_slice(&mut buf[..len]);
+    let base_len = buf[..len].iter().position(|c| *c == b'/').unwrap_or(len);
+    let base = core::str::from_utf8(&buf[..base_len]).ok()?;
+    if base.is_empty() || !base.bytes().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(Sep40Asset::Other(Symbol::new(e, base)))
+}
+
 // --------------------------------------------------------------------------
 // Fetching logic
 // --------------------------------------------------------------------------
@@ -67,93 +142,167 @@ pub struct OracleResult {
     pub from_primary: bool,
 }
 
-/// Fetch a live price from the DIA oracle at `oracle_address` for `pair`
-/// (e.g. `"XLM/USD"`).
-///
-/// # Staleness guard
-/// If the price timestamp is older than `max_age_seconds`, the function
-/// panics with an informative message so the caller can decide to fall back.
-///
-/// # Returns
-/// `OracleResult` with `from_primary = true`.
-pub fn fetch_primary_oracle_price(
+/// Query the price feed at `address` through `adapter`, normalized to 8
+/// decimals.  `None` if the call fails or the price is older than
+/// `max_age_seconds`.  DEX spot prices are always fresh and are the only ones
+/// reported with `from_primary = false`.
+pub fn fetch_adapter_price(
     e: &Env,
-    oracle_address: &Address,
-    pair: String,
+    address: &Address,
+    adapter: &OracleAdapter,
+    pair: &String,
     max_age_seconds: u64,
-) -> OracleResult {
-    let client = DiaOraclePriceClient::new(e, oracle_address);
-    let (raw_price, timestamp) = client.get_value(&pair);
+) -> Option<OracleResult> {
+    let now = e.ledger().timestamp();
+    let (price, timestamp) = match adapter {
+        OracleAdapter::Dia => {
+            let client = DiaOraclePriceClient::new(e, address);
+            match client.try_get_value(pair) {
+                Ok(Ok(value)) => value,
+                _ => return None,
+            }
+        }
+        OracleAdapter::Sep40 => {
+            let client = Sep40OracleClient::new(e, address);
+            let asset = sep40_asset(e, pair)?;
+            let data = match client.try_lastprice(&asset) {
+                Ok(Ok(Some(data))) => data,
+                _ => return None,
+            };
+            let decimals = match client.try_decimals() {
+                Ok(Ok(decimals)) => decimals,
+                _ => return None,
+            };
+            (normalize_price(data.price, decimals)?, data.timestamp)
+        }
+        OracleAdapter::DexRouter => {
+            let client = DexPriceRouterClient::new(e, address);
+            match client.try_get_spot_price(pair) {
+                Ok(Ok(price)) => (price, now),
+                _ => return None,
+            }
+        }
+    };
 
     // Staleness check
-    let now = e.ledger().timestamp();
     if now > timestamp && (now - timestamp) > max_age_seconds {
-        panic!("oracle price is stale");
+        return None;
     }
-
-    OracleResult {
-        price: raw_price,
+    Some(OracleResult {
+        price,
         timestamp,
-        from_primary: true,
-    }
-}
-
-/// Fetch a spot price from the DEX router at `dex_address` for `pair`.
-/// This is the fallback when the primary oracle is unavailable or stale.
-pub fn fetch_dex_price(e: &Env, dex_address: &Address, pair: String) -> OracleResult {
-    let client = DexPriceRouterClient::new(e, dex_address);
-    let raw_price = client.get_spot_price(&pair);
-
-    OracleResult {
-        price: raw_price,
-        timestamp: e.ledger().timestamp(),
-        from_primary: false,
-    }
+        from_primary: *adapter != OracleAdapter::DexRouter,
+    })
 }
 
-/// High-level helper: try the primary oracle first; fall back to the DEX
-/// router if the oracle is stale or panics.
+/// High-level helper: try the primary oracle (spoken to through `adapter`)
+/// first; fall back to the DEX router if the oracle is stale or panics.
 ///
 /// Returns `None` only when both sources are unavailable (to let the caller
 /// choose between using the cached price or panicking).
 pub fn fetch_price_with_fallback(
     e: &Env,
     oracle_address: &Address,
+    adapter: &OracleAdapter,
     dex_address: &Address,
     pair: String,
     max_age_seconds: u64,
 ) -> Option<OracleResult> {
     // --- Primary oracle ---
-    let client = DiaOraclePriceClient::new(e, oracle_address);
-    let oracle_result = client.try_get_value(&pair);
-
-    if let Ok(Ok((raw_price, timestamp))) = oracle_result {
-        let now = e.ledger().timestamp();
-        // Accept if fresh enough
-        if now <= timestamp || (now - timestamp) <= max_age_seconds {
-            return Some(OracleResult {
-                price: raw_price,
-                timestamp,
-                from_primary: true,
-            });
-        }
-        // else fall through to DEX fallback
+    let primary = fetch_adapter_price(e, oracle_address, adapter, &pair, max_age_seconds);
+    if primary.is_some() {
+        return primary;
     }
 
     // --- DEX fallback ---
-    let dex_client = DexPriceRouterClient::new(e, dex_address);
-    let dex_result = dex_client.try_get_spot_price(&pair);
-
-    if let Ok(Ok(raw_price)) = dex_result {
-        return Some(OracleResult {
-            price: raw_price,
-            timestamp: e.ledger().timestamp(),
-            from_primary: false,
-        });
+    fetch_adapter_price(
+        e,
+        dex_address,
+        &OracleAdapter::DexRouter,
+        &pair,
+        max_age_seconds,
+    )
+}
+
+// --------------------------------------------------------------------------
+// Multi-source aggregation
+//
+// With several sources configured the price is the median of the sources that
+// answered with a fresh, positive price.  Too few answers, or answers that
+// spread too far apart, mean the feed can't be trusted right now.
+// --------------------------------------------------------------------------
+
+/// Outcome of querying the configured price sources.
+pub enum PriceFeed {
+    /// Price with 8 decimal places.
+    Price(OracleResult),
+    /// No source answered; pricing carries on without a new sample.
+    Unavailable,
+    /// Below quorum or sources disagree; pricing should hold its current price.
+    Untrusted,
+}
+
+/// Query a single source through its adapter.  `None` if the call fails, the
+/// price is stale or not positive.
+pub fn fetch_source_price(
+    e: &Env,
+    source: &OracleSource,
+    pair: &String,
+    max_age_seconds: u64,
+) -> Option<OracleResult> {
+    fetch_adapter_price(e, &source.address, &source.adapter, pair, max_age_seconds)
+        .filter(|result| result.price > 0)
+}
+
+/// Median of the fresh prices reported by `sources`.
+///
+/// Fewer than `min_quorum` answers, or a spread (max - min) wider than
+/// `max_deviation_bps` of the median (0 = unchecked), make the feed
+/// `Untrusted`.  The median is timestamped with its oldest answer.
+pub fn fetch_median_price(
+    e: &Env,
+    sources: &Vec<OracleSource>,
+    pair: String,
+    max_age_seconds: u64,
+    min_quorum: u32,
+    max_deviation_bps: u32,
+) -> PriceFeed {
+    // Insertion sort as we go; there are only a handful of sources
+    let mut prices: Vec<i128> = Vec::new(e);
+    let mut oldest = e.ledger().timestamp();
+    for source in sources.iter() {
+        if let Some(result) = fetch_source_price(e, &source, &pair, max_age_seconds) {
+            let price = result.price;
+            oldest = oldest.min(result.timestamp);
+            let mut index = prices.len();
+            while index > 0 && prices.get_unchecked(index - 1) > price {
+                index -= 1;
+            }
+            prices.insert(index, price);
+        }
     }
 
-    // Both unavailable
-    None
+    let count = prices.len();
+    if count == 0 {
+        return PriceFeed::Unavailable;
+    }
+    if count < min_quorum {
+        return PriceFeed::Untrusted;
+    }
+    let median = if count % 2 == 1 {
+        prices.get_unchecked(count / 2)
+    } else {
+        (prices.get_unchecked(count / 2 - 1) + prices.get_unchecked(count / 2)) / 2
+    };
+    let spread = prices.get_unchecked(count - 1) - prices.get_unchecked(0);
+    if max_deviation_bps > 0 && spread * 10_000 > median * max_deviation_bps as i128 {
+        return PriceFeed::Untrusted;
+    }
+    PriceFeed::Price(OracleResult {
+        price: median,
+        timestamp: oldest,
+        from_primary: true,
+    })
 }
 
 /// Convert a raw DIA price (8 decimals) into a `ORACLE_PRECISION`-scaled
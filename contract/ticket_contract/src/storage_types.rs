@@ -1,34 +1,140 @@
-use soroban_sdk::{contracttype, Address, String, Symbol};
+use soroban_sdk::{contracttype, Address, BytesN, Map, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
     Admin,
     EventInfo,
-    TokenIdCounter,
     Tier(Symbol),
     Ticket(u32),
     PricingConfig,
+    Quote(Address),
+    WalletMinted(Symbol, Address),
+    Scanner(Address),
+    CheckIn(u32),
+    PresentationKey(Address),
+    PresentationNonce(u32, u64),
+    Escrow(Address),
+    Cancellation,
+    ProceedsWithdrawn,
+    TierRefundSchedule(Symbol),
+    RevenueSplit,
+    Platform,
+    PlatformFee,
+    ResaleConfig,
+    Listing(u32),
+    TransferApproval(u32),
+    TierList,
+    Presale(Symbol),
+    PresaleUsed(Symbol, Address),
+    PromoCode(BytesN<32>),
+    WaitlistConfig(Symbol),
+    WaitlistQueue(Symbol),
+    WaitlistEntry(Symbol, u32),
+    WaitlistSpot(Symbol, Address),
+    Lottery(Symbol),
+    LotteryEntrant(Symbol, u32),
+    LotteryEntry(Symbol, Address),
+    DutchAuction(Symbol),
+    AuctionStep(u32),
+    BondingCurve(Symbol),
+    Experiment(Symbol),
+    ExperimentUsed(Symbol, u32),
+    TicketVariant(u32),
+    OracleObservations,
+    TokenPriceObservations,
+    OracleHealth,
+    AcceptedToken(Address),
+    TotalSupply,
+    IndexLen(TicketIndex),
+    IndexEntries(TicketIndex, u32),
+    IndexSlots(TicketIndex, u32),
+    TierImage(Symbol),
+    TierPriceBounds(Symbol),
+    Seat(u32),
+}
+
+/// Ticket lists maintained by `enumeration.rs`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TicketIndex {
+    Owner(Address),
+    Tier(Symbol),
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum PricingStrategy {
-    Standard,  // Normal demand-curve
-    TimeDecay, // Decreases over time
-    AbTestA,   // High floor
-    AbTestB,   // Higher sensitivity
+    Standard,     // Normal demand-curve
+    TimeDecay,    // Decreases over time
+    AbTestA,      // High floor
+    AbTestB,      // Higher sensitivity
+    DutchAuction, // Steps down to a reserve, see `DutchAuction`
+    BondingCurve, // Parameterized supply curve, see `BondingCurve`
+}
+
+/// Pricing experiment on a tier: every buyer is deterministically assigned
+/// one variant, with probability proportional to its weight.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Experiment {
+    pub id: u32,
+    pub variants: Vec<ExperimentVariant>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExperimentVariant {
+    pub strategy: PricingStrategy,
+    pub weight: u32,
+    /// Tickets sold to buyers in this variant (net of refunds).
+    pub sales: u32,
+    /// What those tickets sold for, net of refunds, per payment token.
+    pub revenue: Map<Address, i128>,
+}
+
+/// Experiment variant a ticket's sale was counted in, so a refund can
+/// reverse it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VariantSale {
+    pub experiment_id: u32,
+    pub variant: u32,
+}
+
+/// Price of the next ticket as a function of tickets already minted, on top
+/// of the tier's base price.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BondingCurve {
+    Linear(i128),     // + slope per ticket minted
+    Exponential(u32), // x (1 + bps / 10000) per ticket minted
+    Step(u32, i128),  // + increment every `step_size` tickets minted
+}
+
+/// Oracle multiplier sampled at `timestamp`.  Samples of a payment token's
+/// price hold the 8-decimal price in `multiplier`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleObservation {
+    pub multiplier: i128,
+    pub timestamp: u64,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PricingConfig {
     pub oracle_address: Address,
+    /// Interface `oracle_address` speaks.
+    pub oracle_adapter: OracleAdapter,
     pub dex_pool_address: Address, // Fallback
+    /// Bounds on ticket prices, in payment token units.  They apply to
+    /// tiers priced in token units without bounds of their own (see
+    /// `set_tier_price_bounds`), never to fiat-priced tiers.
     pub price_floor: i128,
     pub price_ceiling: i128,
-    pub update_frequency: u64, // Seconds
-    pub last_update_time: u64,
+    pub update_frequency: u64, // Minimum seconds between oracle samples
+    pub last_update_time: u64, // When the oracle was last sampled
     pub is_frozen: bool,
     /// Asset pair string to query the oracle, e.g. "XLM/USD".
     pub oracle_pair: String,
@@ -37,6 +143,65 @@ pub struct PricingConfig {
     pub oracle_reference_price: i128,
     /// How old an oracle price can be (seconds) before we fall back to the DEX.
     pub max_oracle_age_seconds: u64,
+    /// When non-empty, price from the median of these sources instead of
+    /// `oracle_address` / `dex_pool_address`.
+    pub oracle_sources: Vec<OracleSource>,
+    /// Fresh answers needed for a median price.
+    pub min_quorum: u32,
+    /// Widest allowed spread between sources, in bps of the median (0 = any).
+    pub max_deviation_bps: u32,
+    /// Circuit breaker: freeze pricing when a new oracle multiplier moves more
+    /// than this many bps away from the previous one (0 = off).
+    pub max_multiplier_move_bps: u32,
+    /// Seconds of oracle history in the time-weighted average; older
+    /// samples expire.
+    pub twap_window_seconds: u64,
+}
+
+/// Where the latest oracle sample came from.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PriceSource {
+    Neutral,     // Nothing sampled yet, the multiplier is 1x
+    Primary,     // `oracle_address`
+    DexFallback, // `dex_pool_address`, the primary was stale or failing
+    Median,      // Median of `oracle_sources`
+    Untrusted,   // Sources below quorum or disagreeing, price is held
+}
+
+/// Latest oracle sample, kept for `price_health`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleHealth {
+    pub source: PriceSource,
+    /// Timestamp the source reported for its price.
+    pub price_timestamp: u64,
+    pub multiplier: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceHealth {
+    pub source: PriceSource,
+    /// Seconds since the source last updated its price.
+    pub age_seconds: u64,
+    pub last_multiplier: i128,
+    pub is_frozen: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OracleAdapter {
+    Dia,       // get_value(pair) -> (price, timestamp)
+    DexRouter, // get_spot_price(pair) -> price
+    Sep40,     // lastprice(asset) -> Option<PriceData>, see `oracle::Sep40OracleClient`
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleSource {
+    pub address: Address,
+    pub adapter: OracleAdapter,
 }
 
 #[contracttype]
@@ -44,6 +209,19 @@ pub struct PricingConfig {
 pub struct EventInfo {
     pub start_time: u64,
     pub refund_cutoff_time: u64,
+    /// Refunds stay open until this time after a `reschedule`, even past the cutoff.
+    pub refund_grace_until: u64,
+    /// Partial refund steps, furthest from the start first (empty = full refund).
+    pub refund_schedule: Vec<RefundStep>,
+}
+
+/// Refund `refund_bps` of `price_paid` while at least `seconds_before_start`
+/// remain until the event starts.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundStep {
+    pub seconds_before_start: u64,
+    pub refund_bps: u32,
 }
 
 #[contracttype]
@@ -56,6 +234,59 @@ pub struct Tier {
     pub minted: u32,
     pub active: bool,
     pub strategy: PricingStrategy,
+    /// Maximum tickets a single wallet may mint from this tier (0 = unlimited).
+    pub max_per_wallet: u32,
+    /// Whether `max_per_wallet` also applies to organizer `batch_mint`.
+    pub cap_batch_mint: bool,
+    pub entry_policy: EntryPolicy,
+    /// Distinct tickets of this tier that have been checked in at least once.
+    pub checked_in: u32,
+    pub transfer_policy: TransferPolicy,
+    /// Sales open at this timestamp (0 = no lower bound).
+    pub sale_start: u64,
+    /// Sales close at this timestamp (0 = no upper bound).
+    pub sale_end: u64,
+    /// Seats held back for outstanding waitlist offers.
+    pub reserved: u32,
+    /// Fiat currency the tier is priced in, in cents (None = priced in
+    /// units of the payment token).
+    pub currency: Option<Symbol>,
+}
+
+/// Payment token accepted for fiat-priced tiers.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AcceptedToken {
+    /// Fiat currency the oracle pair quotes the token in, e.g. "USD".
+    pub currency: Symbol,
+    /// Oracle pair pricing one token in `currency`, e.g. "XLM/USD"
+    /// (None = pegged 1:1, for stablecoins).
+    pub pair: Option<String>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransferPolicy {
+    Soulbound,              // Never transferable
+    TransferableUntil(u64), // Freely giftable until the given timestamp
+    OrganizerApproved,      // Each transfer needs `approve_transfer` from the organizer
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EntryPolicy {
+    SingleEntry,  // One scan per ticket
+    Unlimited,    // Free re-entry
+    Limited(u32), // Up to N scans per ticket
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckInRecord {
+    pub first_entry_time: u64,
+    pub last_entry_time: u64,
+    pub last_scanner: Address,
+    pub entries: u32,
 }
 
 #[contracttype]
@@ -65,4 +296,204 @@ pub struct Ticket {
     pub purchase_time: u64,
     pub price_paid: i128,
     pub is_valid: bool,
+    /// Token `price_paid` was settled in (None for free organizer mints).
+    pub payment_token: Option<Address>,
+}
+
+/// Per payment token escrow accounting.  Ticket revenue is held by the
+/// contract until the event starts; what is still held is
+/// `collected - refunded - withdrawn`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowTotals {
+    pub collected: i128,
+    pub refunded: i128,
+    /// Non-refunded remainder of partial refunds, kept for the organizer.
+    pub retained: i128,
+    pub withdrawn: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cancellation {
+    pub reason: String,
+    pub cancelled_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceQuote {
+    pub tier_symbol: Symbol,
+    /// Ticket price in the tier's own unit (cents for fiat-priced tiers).
+    pub price: i128,
+    pub payment_token: Address,
+    /// What the buyer pays, in `payment_token` units.
+    pub amount: i128,
+    pub expires_at: u64,
+}
+
+/// Cut of withdrawn proceeds owed to the platform, set by the platform
+/// authority rather than the organizer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformFee {
+    pub treasury: Address,
+    pub fee_bps: u32,
+}
+
+/// How withdrawn proceeds are shared with co-organizers.  Next to the
+/// platform fee each payee gets `share_bps`, and the organizer keeps the rest.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevenueSplit {
+    pub payees: Vec<Payee>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payee {
+    pub address: Address,
+    pub share_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResaleConfig {
+    pub enabled: bool,
+    /// Maximum listing price as a share of the ticket's `price_paid` (bps).
+    pub max_price_bps: u32,
+    /// Share of every resale paid to the organizer (bps).
+    pub royalty_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Listing {
+    pub seller: Address,
+    pub price: i128,
+}
+
+/// Allowlisted presale phase of a tier, open during `[start, end)`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Presale {
+    pub merkle_root: BytesN<32>,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Discount {
+    Percent(u32), // Basis points off the dynamic price
+    Fixed(i128),  // Flat amount off the dynamic price
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PromoCode {
+    pub discount: Discount,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: u64,
+    /// Restrict the code to a single tier (None = any tier).
+    pub tier: Option<Symbol>,
+}
+
+/// Descending-price sale of a tier: the price drops from `start_price` to
+/// `reserve_price` in equal steps every `step_seconds` over
+/// `[start_time, end_time]`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DutchAuction {
+    pub start_price: i128,
+    pub reserve_price: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub step_seconds: u64,
+    /// Everyone ends up paying the clearing price: early buyers can claim
+    /// the difference back once the auction is over.
+    pub uniform_clearing: bool,
+    /// Lowest auction step sold at so far, before promo discounts and
+    /// oracle multipliers (0 = no sales yet).
+    pub clearing_price: i128,
+}
+
+/// Price bounds of one tier, in the tier's own unit (cents for fiat-priced
+/// tiers).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceBounds {
+    pub floor: i128,
+    pub ceiling: i128,
+}
+
+/// Lottery sale of a tier: entries are taken during `[start, end)`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lottery {
+    pub payment_token: Address,
+    /// Ticket price, escrowed in full as the entry deposit.
+    pub price: i128,
+    pub start: u64,
+    pub end: u64,
+    /// How long winners have to claim once the draw is complete.
+    pub claim_window: u64,
+    /// Number of entries, stored under `LotteryEntrant(tier, 0..entrants)`.
+    pub entrants: u32,
+    /// Seats awarded, fixed by the first `settle_lottery` call.
+    pub winners: u32,
+    /// Winners drawn so far.
+    pub drawn: u32,
+    pub settled: bool,
+    /// End of the claim window (0 until settled).
+    pub claim_deadline: u64,
+    pub claimed: u32,
+    /// Whether unclaimed seats have gone back on sale.
+    pub released: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WaitlistConfig {
+    /// Token the join deposit is paid in (None = no deposit).
+    pub deposit_token: Option<Address>,
+    pub deposit: i128,
+    /// How long an offered seat stays exclusive to its holder.
+    pub offer_window: u64,
+}
+
+/// FIFO queue of a tier's waitlist, kept as a doubly linked list of
+/// `WaitlistNode`s so that leaving unlinks an entry in place.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WaitlistQueue {
+    /// Position of the first and last address in line (None = empty).
+    pub head: Option<u32>,
+    pub tail: Option<u32>,
+    /// Position handed to the next address that joins.
+    pub next_position: u32,
+    /// Addresses waiting in line (not yet offered a seat).
+    pub length: u32,
+    /// Addresses currently holding an offer.
+    pub offers: Vec<Address>,
+}
+
+/// An address waiting in line, stored under `DataKey::WaitlistEntry`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WaitlistNode {
+    pub holder: Address,
+    pub prev: Option<u32>,
+    pub next: Option<u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WaitlistSpot {
+    pub position: u32,
+    pub deposit_token: Option<Address>,
+    pub deposit: i128,
+    /// Deadline of the outstanding offer (0 = still waiting in line).
+    pub offer_expires_at: u64,
 }
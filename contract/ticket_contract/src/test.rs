@@ -2,7 +2,12 @@
 extern crate std;
 
 use super::*;
-use soroban_sdk::{contract, contractimpl, testutils::Address as _, Address, Env, String, Symbol};
+use soroban_sdk::{
+    contract, contractimpl, map, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token, Address, Bytes, BytesN, Env, Map, String, Symbol, TryFromVal,
+};
+use storage_types::{OracleSource, Payee};
 
 // ---------------------------------------------------------------------------
 // Mock Oracle Contract
@@ -23,6 +28,69 @@ impl MockOracle {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Mock SEP-40 Oracle Contract
+//
+// Reflector-style feed: lastprice(asset) -> Option<PriceData> with 14
+// decimals.  Only knows XLM, priced at $1.20.
+// ---------------------------------------------------------------------------
+#[contract]
+pub struct MockSep40Oracle;
+
+#[contractimpl]
+impl MockSep40Oracle {
+    pub fn lastprice(env: Env, asset: oracle::Sep40Asset) -> Option<oracle::Sep40PriceData> {
+        if asset != oracle::Sep40Asset::Other(Symbol::new(&env, "XLM")) {
+            return None;
+        }
+        Some(oracle::Sep40PriceData {
+            price: 120_000_000_000_000,
+            timestamp: env.ledger().timestamp(),
+        })
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        14
+    }
+}
+
+/// DIA-style oracle whose price can be changed between calls.
+#[contract]
+pub struct MockSettableOracle;
+
+#[contractimpl]
+impl MockSettableOracle {
+    pub fn set_price(env: Env, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+    }
+
+    /// Offline oracles fail every query.
+    pub fn set_offline(env: Env, offline: bool) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("offline"), &offline);
+    }
+
+    pub fn get_value(env: Env, _pair: String) -> (i128, u64) {
+        let offline: bool = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("offline"))
+            .unwrap_or(false);
+        if offline {
+            panic!("oracle offline");
+        }
+        let price: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap();
+        (price, env.ledger().timestamp())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Mock DEX Price Router Contract
 //
@@ -45,15 +113,23 @@ fn create_contract(e: &Env, admin: &Address) -> SoulboundTicketContractClient<'s
 
     client.initialize(
         admin,
+        &Address::generate(e),
         &String::from_str(e, "EventTicket"),
         &String::from_str(e, "TKT"),
         &String::from_str(e, "https://example.com"),
-        &e.ledger().timestamp(),
+        &(e.ledger().timestamp() + 100000),
         &(e.ledger().timestamp() + 100000), // Refund cutoff
     );
     client
 }
 
+fn create_payment_token<'a>(e: &Env, holder: &Address, amount: i128) -> token::Client<'a> {
+    let issuer = Address::generate(e);
+    let sac = e.register_stellar_asset_contract_v2(issuer);
+    token::StellarAssetClient::new(e, &sac.address()).mint(holder, &amount);
+    token::Client::new(e, &sac.address())
+}
+
 #[test]
 fn test_initialize_and_tier_creation() {
     let e = Env::default();
@@ -123,7 +199,7 @@ fn test_soulbound_restriction() {
     client.batch_mint(&user1, &tier_sym, &1);
 
     // This should panic
-    client.transfer(&user1, &user2, &1);
+    client.transfer(&user1, &user2, &0);
 }
 
 #[test]
@@ -216,6 +292,7 @@ fn test_emergency_freeze_and_bounds() {
 
     let config = PricingConfig {
         oracle_address: admin.clone(), // admin == neutral (no oracle configured), returns ORACLE_PRECISION
+        oracle_adapter: OracleAdapter::Dia,
         dex_pool_address: admin.clone(),
         price_floor: 50,
         price_ceiling: 150,
@@ -225,6 +302,11 @@ fn test_emergency_freeze_and_bounds() {
         oracle_pair: String::from_str(&e, "XLM/USD"),
         oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
         max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
     };
     client.set_pricing_config(&config);
 
@@ -287,6 +369,7 @@ fn test_oracle_multiplier_integration() {
     // Point the PricingConfig at the mock oracle
     let config = PricingConfig {
         oracle_address: oracle_id.clone(),
+        oracle_adapter: OracleAdapter::Dia,
         dex_pool_address: dex_id.clone(),
         price_floor: 0,
         price_ceiling: i128::MAX,
@@ -297,6 +380,11 @@ fn test_oracle_multiplier_integration() {
         // $1.00 baseline in 8-decimal format
         oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
         max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
     };
     client.set_pricing_config(&config);
 
@@ -341,3 +429,2869 @@ fn test_oracle_fallback_neutral_when_unconfigured() {
     // No oracle configured → price should equal base price
     assert_eq!(client.get_ticket_price(&tier_sym), 200);
 }
+
+#[test]
+fn test_purchase_within_max_price() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+
+    assert_eq!(client.balance(&buyer), 1);
+    assert_eq!(client.get_ticket(&0).price_paid, 100);
+    assert_eq!(payment.balance(&buyer), 900);
+}
+
+#[test]
+#[should_panic(expected = "Price exceeds max_price")]
+fn test_purchase_rejects_price_above_max() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+
+    // Demand pushes the price to 105 between the quote the user saw and execution
+    client.batch_mint(&Address::generate(&e), &tier_sym, &2);
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+}
+
+#[test]
+fn test_quote_locks_price() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+
+    let quote = client.quote_price(&buyer, &tier_sym, &payment.address);
+    assert_eq!(quote.price, 100);
+
+    // Price moves to 105 after the quote was issued
+    client.batch_mint(&Address::generate(&e), &tier_sym, &2);
+    assert_eq!(client.get_ticket_price(&tier_sym), 105);
+
+    client.purchase_with_quote(&buyer, &payment.address, &tier_sym);
+    assert_eq!(payment.balance(&buyer), 900);
+    assert_eq!(client.get_quote(&buyer), None);
+}
+
+#[test]
+#[should_panic(expected = "Quote expired")]
+fn test_expired_quote_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+
+    let quote = client.quote_price(&buyer, &tier_sym, &payment.address);
+    e.ledger().set_timestamp(quote.expires_at + 1);
+    client.purchase_with_quote(&buyer, &payment.address, &tier_sym);
+}
+
+#[test]
+fn test_purchase_many_walks_demand_curve() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    ); // thresholds every 2 tickets
+
+    // 100 + 100 + 105: the third ticket crosses the first threshold
+    assert_eq!(client.quote_total_price(&tier_sym, &3), 305);
+
+    client.purchase_many(&buyer, &payment.address, &tier_sym, &3, &305);
+
+    assert_eq!(client.balance(&buyer), 3);
+    assert_eq!(payment.balance(&buyer), 695);
+    assert_eq!(client.get_ticket(&2).price_paid, 105);
+    assert_eq!(client.wallet_minted(&tier_sym, &buyer), 3);
+}
+
+#[test]
+#[should_panic(expected = "Exceeds per-wallet limit")]
+fn test_wallet_cap_enforced_across_purchases() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.set_wallet_cap(&tier_sym, &2, &false);
+
+    client.purchase_many(&buyer, &payment.address, &tier_sym, &2, &200);
+    // Third ticket from the same wallet is rejected
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+}
+
+#[test]
+fn test_wallet_cap_on_batch_mint_is_opt_in() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &50,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    // Cap not applied to organizer mints
+    client.set_wallet_cap(&tier_sym, &2, &false);
+    client.batch_mint(&user, &tier_sym, &5);
+    assert_eq!(client.balance(&user), 5);
+
+    // Once enabled, organizer mints count towards the cap too
+    client.set_wallet_cap(&tier_sym, &2, &true);
+    let capped = Address::generate(&e);
+    client.batch_mint(&capped, &tier_sym, &2);
+    assert!(client.try_batch_mint(&capped, &tier_sym, &1).is_err());
+}
+
+#[test]
+fn test_check_in_single_entry() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let scanner = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &50,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.batch_mint(&user, &tier_sym, &2);
+
+    // Unregistered scanners cannot check tickets in
+    assert!(client.try_check_in(&scanner, &1).is_err());
+
+    client.add_scanner(&scanner);
+    client.check_in(&scanner, &1);
+
+    let record = client.get_check_in(&1).unwrap();
+    assert_eq!(record.entries, 1);
+    assert_eq!(record.last_scanner, scanner);
+    assert_eq!(client.checked_in_count(&tier_sym), 1);
+
+    // Second scan of the same ticket is rejected at every door
+    let other_door = Address::generate(&e);
+    client.add_scanner(&other_door);
+    assert!(client.try_check_in(&other_door, &1).is_err());
+
+    client.remove_scanner(&scanner);
+    assert!(!client.is_scanner(&scanner));
+}
+
+#[test]
+fn test_check_in_limited_reentry() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let scanner = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "VIP"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_entry_policy(&tier_sym, &EntryPolicy::Limited(2));
+    client.batch_mint(&user, &tier_sym, &1);
+    client.add_scanner(&scanner);
+
+    client.check_in(&scanner, &0);
+    client.check_in(&scanner, &0);
+    assert_eq!(client.get_check_in(&0).unwrap().entries, 2);
+    // Re-entry only counts the ticket once
+    assert_eq!(client.checked_in_count(&tier_sym), 1);
+
+    assert!(client.try_check_in(&scanner, &0).is_err());
+}
+
+fn sign_presentation(
+    e: &Env,
+    client: &SoulboundTicketContractClient,
+    signer: &ed25519_dalek::SigningKey,
+    token_id: u32,
+    nonce: u64,
+    expiry: u64,
+) -> BytesN<64> {
+    use ed25519_dalek::Signer;
+
+    let challenge = client.presentation_challenge(&token_id, &nonce, &expiry);
+    let mut message = std::vec![0u8; challenge.len() as usize];
+    challenge.copy_into_slice(&mut message);
+    BytesN::from_array(e, &signer.sign(&message).to_bytes())
+}
+
+#[test]
+fn test_verify_presentation_and_replay_protection() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &50,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.batch_mint(&holder, &tier_sym, &1);
+
+    let signer = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+    client.register_presentation_key(
+        &holder,
+        &BytesN::from_array(&e, &signer.verifying_key().to_bytes()),
+    );
+
+    let expiry = e.ledger().timestamp() + 60;
+    let signature = sign_presentation(&e, &client, &signer, 0, 42, expiry);
+    assert_eq!(
+        client.verify_presentation(&0, &42, &expiry, &signature),
+        holder
+    );
+
+    // The same QR code cannot be presented twice
+    assert!(client
+        .try_verify_presentation(&0, &42, &expiry, &signature)
+        .is_err());
+
+    // A key that is not the holder's cannot produce a valid proof
+    let impostor = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+    let forged = sign_presentation(&e, &client, &impostor, 0, 43, expiry);
+    assert!(client
+        .try_verify_presentation(&0, &43, &expiry, &forged)
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "Presentation expired")]
+fn test_verify_presentation_rejects_expired_challenge() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &50,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.batch_mint(&holder, &tier_sym, &1);
+
+    let signer = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+    client.register_presentation_key(
+        &holder,
+        &BytesN::from_array(&e, &signer.verifying_key().to_bytes()),
+    );
+
+    let expiry = e.ledger().timestamp() + 60;
+    let signature = sign_presentation(&e, &client, &signer, 1, 1, expiry);
+    e.ledger().set_timestamp(expiry + 1);
+    client.verify_presentation(&1, &1, &expiry, &signature);
+}
+
+#[test]
+fn test_cancel_event_refunds_after_cutoff() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.purchase_many(&buyer, &payment.address, &tier_sym, &2, &200);
+
+    // Funds are held in escrow, not paid out to the organizer
+    assert_eq!(payment.balance(&client.address), 200);
+    assert_eq!(payment.balance(&admin), 0);
+
+    // Past the normal refund cutoff
+    e.ledger().set_timestamp(100_001);
+    assert!(client.try_refund(&buyer, &payment.address, &1).is_err());
+
+    client.cancel_event(&String::from_str(&e, "Venue unavailable"));
+    let (_, topics, data) = e.events().all().last().unwrap();
+    let name = Symbol::try_from_val(&e, &topics.get(0).unwrap()).unwrap();
+    assert_eq!(name, Symbol::new(&e, "cancelled"));
+    let reason = String::try_from_val(&e, &data).unwrap();
+    assert_eq!(reason, String::from_str(&e, "Venue unavailable"));
+    assert!(client.get_cancellation().is_some());
+
+    client.refund(&buyer, &payment.address, &1);
+    assert_eq!(payment.balance(&buyer), 900);
+
+    let totals = client.get_escrow_totals(&payment.address);
+    assert_eq!(totals.collected, 200);
+    assert_eq!(totals.refunded, 100);
+    assert_eq!(client.outstanding(&payment.address), 100);
+
+    // Sales are frozen and proceeds cannot be withdrawn
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &tier_sym, &100, &None)
+        .is_err());
+    assert!(client.try_withdraw_proceeds(&payment.address).is_err());
+}
+
+#[test]
+fn test_initialize_rejects_refund_cutoff_after_start() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let contract_id = e.register(SoulboundTicketContract, ());
+    let client = SoulboundTicketContractClient::new(&e, &contract_id);
+    // Refunds would outlive the escrow once proceeds unlock at the start
+    assert!(client
+        .try_initialize(
+            &admin,
+            &Address::generate(&e),
+            &String::from_str(&e, "EventTicket"),
+            &String::from_str(&e, "TKT"),
+            &String::from_str(&e, "https://example.com"),
+            &1_000,
+            &1_001,
+        )
+        .is_err());
+}
+
+#[test]
+fn test_withdraw_proceeds_after_event_start() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let contract_id = e.register(SoulboundTicketContract, ());
+    let client = SoulboundTicketContractClient::new(&e, &contract_id);
+    client.initialize(
+        &admin,
+        &Address::generate(&e),
+        &String::from_str(&e, "EventTicket"),
+        &String::from_str(&e, "TKT"),
+        &String::from_str(&e, "https://example.com"),
+        &1_000,
+        &500,
+    );
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+
+    // Locked until the event starts
+    assert!(client.try_withdraw_proceeds(&payment.address).is_err());
+
+    e.ledger().set_timestamp(1_000);
+    assert_eq!(client.withdraw_proceeds(&payment.address), 100);
+    assert_eq!(payment.balance(&admin), 100);
+    assert_eq!(client.outstanding(&payment.address), 0);
+
+    // Cancelling after payout would leave refunds uncovered
+    assert!(client
+        .try_cancel_event(&String::from_str(&e, "Too late"))
+        .is_err());
+}
+
+#[test]
+fn test_reschedule_reopens_refunds_and_reanchors_pricing() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let contract_id = e.register(SoulboundTicketContract, ());
+    let client = SoulboundTicketContractClient::new(&e, &contract_id);
+    client.initialize(
+        &admin,
+        &Address::generate(&e),
+        &String::from_str(&e, "EventTicket"),
+        &String::from_str(&e, "TKT"),
+        &String::from_str(&e, "https://example.com"),
+        &700_000,
+        &650_000,
+    );
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "EARLY");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Early"),
+        &100,
+        &10,
+        &PricingStrategy::TimeDecay,
+    );
+
+    e.ledger().set_timestamp(660_000);
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+    // Past the original cutoff: no refunds
+    assert!(client.try_refund(&buyer, &payment.address, &0).is_err());
+
+    // Move the event three weeks later with an already-passed cutoff
+    let new_start = 700_000 + 21 * 86_400;
+    client.reschedule(&new_start, &600_000);
+
+    let info = client.get_event_info();
+    assert_eq!(info.start_time, new_start);
+    assert_eq!(info.refund_grace_until, 660_000 + 7 * 86_400);
+
+    // Early-bird discount now applies again relative to the new start
+    assert_eq!(client.get_ticket_price(&tier_sym), 90);
+
+    // Grace window lets the holder get their money back
+    client.refund(&buyer, &payment.address, &0);
+    assert_eq!(payment.balance(&buyer), 1_000);
+
+    // After the grace window closes refunds are rejected again
+    client.purchase(&buyer, &payment.address, &tier_sym, &90, &None);
+    e.ledger().set_timestamp(info.refund_grace_until + 1);
+    assert!(client.try_refund(&buyer, &payment.address, &1).is_err());
+}
+
+#[test]
+fn test_partial_refund_schedule() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let day = 86_400u64;
+    let start = 40 * day;
+    let contract_id = e.register(SoulboundTicketContract, ());
+    let client = SoulboundTicketContractClient::new(&e, &contract_id);
+    client.initialize(
+        &admin,
+        &Address::generate(&e),
+        &String::from_str(&e, "EventTicket"),
+        &String::from_str(&e, "TKT"),
+        &String::from_str(&e, "https://example.com"),
+        &start,
+        &start,
+    );
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    // 100% until 30 days out, 50% until 7 days out, nothing after
+    client.set_refund_schedule(&vec![
+        &e,
+        RefundStep {
+            seconds_before_start: 30 * day,
+            refund_bps: 10000,
+        },
+        RefundStep {
+            seconds_before_start: 7 * day,
+            refund_bps: 5000,
+        },
+    ]);
+    client.purchase_many(&buyer, &payment.address, &tier_sym, &2, &200);
+
+    assert_eq!(client.preview_refund(&0), 100);
+
+    e.ledger().set_timestamp(start - 20 * day);
+    assert_eq!(client.preview_refund(&0), 50);
+    client.refund(&buyer, &payment.address, &0);
+    assert_eq!(payment.balance(&buyer), 850);
+
+    // The kept half is the organizer's even before the event starts
+    let totals = client.get_escrow_totals(&payment.address);
+    assert_eq!(totals.refunded, 50);
+    assert_eq!(totals.retained, 50);
+    assert_eq!(client.withdrawable(&payment.address), 50);
+
+    e.ledger().set_timestamp(start - 3 * day);
+    assert_eq!(client.preview_refund(&1), 0);
+    assert!(client.try_refund(&buyer, &payment.address, &1).is_err());
+}
+
+#[test]
+fn test_tier_refund_schedule_overrides_event_schedule() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let vip = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &vip,
+        &String::from_str(&e, "VIP"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.purchase(&buyer, &payment.address, &vip, &100, &None);
+
+    // Event-wide refunds are full, but VIP only refunds 25% up to the start
+    client.set_refund_schedule(&vec![
+        &e,
+        RefundStep {
+            seconds_before_start: 0,
+            refund_bps: 10000,
+        },
+    ]);
+    client.set_tier_refund_schedule(
+        &vip,
+        &vec![
+            &e,
+            RefundStep {
+                seconds_before_start: 0,
+                refund_bps: 2500,
+            },
+        ],
+    );
+    assert_eq!(client.preview_refund(&0), 25);
+
+    // Removing the override falls back to the event-wide schedule
+    client.set_tier_refund_schedule(&vip, &Vec::new(&e));
+    assert_eq!(client.preview_refund(&0), 100);
+
+    // The preview applies the same checks as `refund`
+    let scanner = Address::generate(&e);
+    client.add_scanner(&scanner);
+    client.check_in(&scanner, &0);
+    assert_eq!(client.preview_refund(&0), 0);
+    assert!(client.try_refund(&buyer, &payment.address, &0).is_err());
+
+    // Unordered steps are rejected
+    assert!(client
+        .try_set_refund_schedule(&vec![
+            &e,
+            RefundStep {
+                seconds_before_start: 10,
+                refund_bps: 5000,
+            },
+            RefundStep {
+                seconds_before_start: 20,
+                refund_bps: 10000,
+            },
+        ])
+        .is_err());
+}
+
+#[test]
+fn test_revenue_split_on_withdrawal() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let treasury = Address::generate(&e);
+    let venue = Address::generate(&e);
+    let speaker = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 10_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &1_000,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    // 2.5% platform fee, 20% venue, 10% speaker, organizer keeps 67.5%
+    client.set_platform_fee(&PlatformFee {
+        treasury: treasury.clone(),
+        fee_bps: 250,
+    });
+    // The fee is the platform's to set, not the organizer's
+    assert_eq!(e.auths()[0].0, client.get_platform());
+    assert_ne!(client.get_platform(), admin);
+    client.set_revenue_split(&RevenueSplit {
+        payees: vec![
+            &e,
+            Payee {
+                address: venue.clone(),
+                share_bps: 2000,
+            },
+            Payee {
+                address: speaker.clone(),
+                share_bps: 1000,
+            },
+        ],
+    });
+
+    client.purchase_many(&buyer, &payment.address, &tier_sym, &3, &3_000);
+    // A refunded ticket is reversed from every share
+    client.refund(&buyer, &payment.address, &2);
+
+    e.ledger().set_timestamp(100_000);
+    assert_eq!(client.withdraw_proceeds(&payment.address), 2_000);
+    assert_eq!(payment.balance(&treasury), 50);
+    assert_eq!(payment.balance(&venue), 400);
+    assert_eq!(payment.balance(&speaker), 200);
+    assert_eq!(payment.balance(&admin), 1_350);
+    assert_eq!(payment.balance(&client.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "Revenue shares exceed 100%")]
+fn test_revenue_split_rejects_over_allocation() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    client.set_platform_fee(&PlatformFee {
+        treasury: Address::generate(&e),
+        fee_bps: 500,
+    });
+    client.set_revenue_split(&RevenueSplit {
+        payees: vec![
+            &e,
+            Payee {
+                address: Address::generate(&e),
+                share_bps: 9600,
+            },
+        ],
+    });
+}
+
+#[test]
+fn test_resale_with_cap_and_royalty() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let seller = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &seller, 1_000);
+    token::StellarAssetClient::new(&e, &payment.address).mint(&buyer, &1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.purchase(&seller, &payment.address, &tier_sym, &100, &None);
+
+    // Resale is opt-in
+    assert!(client.try_list_for_resale(&seller, &0, &100).is_err());
+
+    client.set_resale_config(&ResaleConfig {
+        enabled: true,
+        max_price_bps: 11000,
+        royalty_bps: 500,
+    });
+
+    // Soulbound tiers (the default) cannot be resold
+    assert!(client.try_list_for_resale(&seller, &0, &100).is_err());
+    client.set_transfer_policy(&tier_sym, &TransferPolicy::TransferableUntil(100_000));
+
+    // Above 110% of face value is rejected
+    assert!(client.try_list_for_resale(&seller, &0, &111).is_err());
+    client.list_for_resale(&seller, &0, &110);
+
+    client.buy_resale(&buyer, &0, &110);
+
+    assert_eq!(client.owner_of(&0), buyer);
+    assert_eq!(client.balance(&seller), 0);
+    assert_eq!(client.get_listing(&0), None);
+    assert_eq!(payment.balance(&buyer), 890);
+    assert_eq!(payment.balance(&admin), 5);
+    assert_eq!(payment.balance(&seller), 1_005);
+
+    // The ticket record still reflects the escrowed face value
+    let ticket = client.get_ticket(&0);
+    assert_eq!(ticket.price_paid, 100);
+    assert!(ticket.is_valid);
+}
+
+#[test]
+fn test_stale_listing_after_refund() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let seller = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &seller, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_resale_config(&ResaleConfig {
+        enabled: true,
+        max_price_bps: 10000,
+        royalty_bps: 0,
+    });
+    client.set_transfer_policy(&tier_sym, &TransferPolicy::TransferableUntil(100_000));
+    client.purchase(&seller, &payment.address, &tier_sym, &100, &None);
+    client.list_for_resale(&seller, &0, &100);
+
+    client.refund(&seller, &payment.address, &0);
+    assert_eq!(client.get_listing(&0), None);
+    assert!(client.try_buy_resale(&buyer, &0, &100).is_err());
+}
+
+#[test]
+fn test_transferable_until_cutoff() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "GA");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General Admission"),
+        &50,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.set_transfer_policy(&tier_sym, &TransferPolicy::TransferableUntil(1_000));
+    client.batch_mint(&user1, &tier_sym, &2);
+
+    client.transfer(&user1, &user2, &0);
+    // Wallets and indexers see the move as a SEP-50 transfer
+    let (_, topics, _) = e.events().all().last().unwrap();
+    let name = Symbol::try_from_val(&e, &topics.get(0).unwrap()).unwrap();
+    assert_eq!(name, Symbol::new(&e, "transfer"));
+    assert_eq!(client.owner_of(&0), user2);
+
+    // Approved spenders can move tickets too
+    let spender = Address::generate(&e);
+    client.approve(&user1, &spender, &1, &1_000);
+    assert_eq!(client.get_approved(&1), Some(spender.clone()));
+    client.transfer_from(&spender, &user1, &user2, &1);
+    assert_eq!(client.balance(&user2), 2);
+
+    e.ledger().set_timestamp(1_001);
+    assert!(client.try_transfer(&user2, &user1, &0).is_err());
+}
+
+#[test]
+fn test_organizer_approved_transfer() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "SPK");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Speaker"),
+        &0,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_transfer_policy(&tier_sym, &TransferPolicy::OrganizerApproved);
+    client.batch_mint(&user1, &tier_sym, &1);
+
+    assert!(client.try_transfer(&user1, &user2, &0).is_err());
+
+    client.approve_transfer(&0, &user2);
+    client.transfer(&user1, &user2, &0);
+    assert_eq!(client.owner_of(&0), user2);
+
+    // The approval was single use
+    assert!(client.try_transfer(&user2, &user1, &0).is_err());
+}
+
+#[test]
+fn test_tier_lifecycle_and_listing() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let gen = Symbol::new(&e, "GEN");
+    let vip = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &gen,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.add_tier(
+        &vip,
+        &String::from_str(&e, "VIP"),
+        &300,
+        &5,
+        &PricingStrategy::Standard,
+    );
+
+    client.update_tier(&gen, &String::from_str(&e, "General Admission"), &80, &20);
+    client.set_tier_active(&vip, &false);
+
+    let tiers = client.list_tiers();
+    assert_eq!(tiers.len(), 2);
+    let (symbol, tier) = tiers.get(0).unwrap();
+    assert_eq!(symbol, gen);
+    assert_eq!(tier.name, String::from_str(&e, "General Admission"));
+    assert_eq!(tier.max_supply, 20);
+    assert_eq!(client.get_ticket_price(&gen), 80);
+    assert!(!tiers.get(1).unwrap().1.active);
+
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &vip, &300, &None)
+        .is_err());
+
+    // Supply cannot shrink below what has been sold
+    client.purchase_many(&buyer, &payment.address, &gen, &3, &240);
+    assert!(client
+        .try_update_tier(&gen, &String::from_str(&e, "General"), &80, &2)
+        .is_err());
+}
+
+#[test]
+fn test_sale_window_enforced() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "DROP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Timed Drop"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_sale_window(&tier_sym, &1_000, &2_000);
+
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &tier_sym, &100, &None)
+        .is_err());
+
+    e.ledger().set_timestamp(1_000);
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+
+    e.ledger().set_timestamp(2_000);
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &tier_sym, &100, &None)
+        .is_err());
+}
+
+#[test]
+fn test_presale_with_merkle_allowlist() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let alice = Address::generate(&e);
+    let bob = Address::generate(&e);
+    let mallory = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &alice, 1_000);
+    let token_admin = token::StellarAssetClient::new(&e, &payment.address);
+    token_admin.mint(&bob, &1_000);
+    token_admin.mint(&mallory, &1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    // Two-leaf tree: alice may buy 2, bob has no per-address allocation
+    let alice_leaf = merkle::leaf_hash(&e, &alice, 2);
+    let bob_leaf = merkle::leaf_hash(&e, &bob, 0);
+    let root = merkle::hash_pair(&e, &alice_leaf, &bob_leaf);
+    client.set_presale(
+        &tier_sym,
+        &Presale {
+            merkle_root: root,
+            start: 0,
+            end: 1_000,
+        },
+    );
+
+    client.purchase_presale(
+        &alice,
+        &payment.address,
+        &tier_sym,
+        &2,
+        &200,
+        &2,
+        &vec![&e, bob_leaf.clone()],
+    );
+    assert_eq!(client.presale_used(&tier_sym, &alice), 2);
+
+    // Allocation is used up
+    assert!(client
+        .try_purchase_presale(
+            &alice,
+            &payment.address,
+            &tier_sym,
+            &1,
+            &100,
+            &2,
+            &vec![&e, bob_leaf.clone()],
+        )
+        .is_err());
+
+    client.purchase_presale(
+        &bob,
+        &payment.address,
+        &tier_sym,
+        &3,
+        &300,
+        &0,
+        &vec![&e, alice_leaf.clone()],
+    );
+    assert_eq!(client.balance(&bob), 3);
+
+    // Not on the list, and the public sale is closed during the presale
+    assert!(client
+        .try_purchase_presale(
+            &mallory,
+            &payment.address,
+            &tier_sym,
+            &1,
+            &100,
+            &0,
+            &vec![&e, alice_leaf.clone()],
+        )
+        .is_err());
+    assert!(client
+        .try_purchase(&mallory, &payment.address, &tier_sym, &100, &None)
+        .is_err());
+
+    e.ledger().set_timestamp(1_000);
+    client.purchase(&mallory, &payment.address, &tier_sym, &105, &None);
+    assert_eq!(client.balance(&mallory), 1);
+}
+
+#[test]
+fn test_promo_code_discount_and_limits() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let gen = Symbol::new(&e, "GEN");
+    let vip = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &gen,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.add_tier(
+        &vip,
+        &String::from_str(&e, "VIP"),
+        &300,
+        &10,
+        &PricingStrategy::Standard,
+    );
+
+    let code = Bytes::from_slice(&e, b"SPEAKER50");
+    let code_hash: BytesN<32> = e.crypto().sha256(&code).into();
+    client.add_promo_code(
+        &code_hash,
+        &Discount::Percent(5000),
+        &2,
+        &1_000,
+        &Some(gen.clone()),
+    );
+
+    client.purchase(&buyer, &payment.address, &gen, &50, &Some(code.clone()));
+    assert_eq!(client.get_ticket(&0).price_paid, 50);
+    assert_eq!(client.get_promo_code(&code_hash).unwrap().uses, 1);
+
+    // Restricted to the GEN tier
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &vip, &300, &Some(code.clone()))
+        .is_err());
+
+    // Wrong codes are rejected rather than silently ignored
+    assert!(client
+        .try_purchase(
+            &buyer,
+            &payment.address,
+            &gen,
+            &100,
+            &Some(Bytes::from_slice(&e, b"NOPE")),
+        )
+        .is_err());
+
+    client.purchase(&buyer, &payment.address, &gen, &50, &Some(code.clone()));
+    // Usage limit reached
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &gen, &100, &Some(code.clone()))
+        .is_err());
+}
+
+#[test]
+fn test_promo_code_respects_price_floor_and_expiry() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: admin.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 70,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: e.ledger().timestamp(),
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    });
+
+    let code = Bytes::from_slice(&e, b"FRIENDS");
+    let code_hash: BytesN<32> = e.crypto().sha256(&code).into();
+    client.add_promo_code(&code_hash, &Discount::Fixed(50), &10, &500, &None);
+
+    // 100 - 50 would be 50, but the floor is 70
+    client.purchase(
+        &buyer,
+        &payment.address,
+        &tier_sym,
+        &70,
+        &Some(code.clone()),
+    );
+    assert_eq!(client.get_ticket(&0).price_paid, 70);
+
+    // The discounted sale does not become the frozen price
+    client.purchase(&buyer, &payment.address, &tier_sym, &100, &None);
+    client.add_promo_code(
+        &e.crypto().sha256(&Bytes::from_slice(&e, b"COMP")).into(),
+        &Discount::Percent(10000),
+        &1,
+        &500,
+        &None,
+    );
+    client.purchase(
+        &buyer,
+        &payment.address,
+        &tier_sym,
+        &70,
+        &Some(Bytes::from_slice(&e, b"COMP")),
+    );
+    client.emergency_freeze(&true);
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+    let other = Address::generate(&e);
+    payment.transfer(&buyer, &other, &100);
+    assert!(client
+        .try_purchase(&other, &payment.address, &tier_sym, &0, &None)
+        .is_err());
+    client.purchase(&other, &payment.address, &tier_sym, &100, &None);
+    assert_eq!(client.get_ticket(&3).price_paid, 100);
+    client.emergency_freeze(&false);
+
+    e.ledger().set_timestamp(501);
+    assert!(client
+        .try_purchase(&buyer, &payment.address, &tier_sym, &100, &Some(code))
+        .is_err());
+}
+
+#[test]
+fn test_waitlist_offer_on_refund() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let waiter = Address::generate(&e);
+    let later = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &holder, 1_000);
+    payment.transfer(&holder, &waiter, &300);
+    payment.transfer(&holder, &later, &300);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &1,
+        &PricingStrategy::Standard,
+    );
+    client.set_waitlist_config(
+        &tier_sym,
+        &WaitlistConfig {
+            deposit_token: Some(payment.address.clone()),
+            deposit: 10,
+            offer_window: 600,
+        },
+    );
+
+    // Waitlists only open once the tier is sold out
+    assert!(client.try_join_waitlist(&waiter, &tier_sym).is_err());
+    client.purchase(&holder, &payment.address, &tier_sym, &100, &None);
+    client.join_waitlist(&waiter, &tier_sym);
+    client.join_waitlist(&later, &tier_sym);
+    assert!(client.try_join_waitlist(&waiter, &tier_sym).is_err());
+    assert_eq!(client.waitlist_length(&tier_sym), 2);
+    assert_eq!(payment.balance(&waiter), 290);
+
+    // The refunded seat is held for the head of the line, not the public
+    client.refund(&holder, &payment.address, &0);
+    let spot = client.get_waitlist_spot(&tier_sym, &waiter).unwrap();
+    assert_eq!(spot.offer_expires_at, 600);
+    assert_eq!(client.list_tiers().get(0).unwrap().1.reserved, 1);
+    assert!(client
+        .try_purchase(&holder, &payment.address, &tier_sym, &200, &None)
+        .is_err());
+    assert!(client
+        .try_claim_waitlist_offer(&later, &payment.address, &tier_sym, &200)
+        .is_err());
+
+    // The refund does not rewind demand pricing: the seat sells at 105
+    client.claim_waitlist_offer(&waiter, &payment.address, &tier_sym, &105);
+    assert_eq!(client.owner_of(&1), waiter);
+    assert_eq!(client.get_waitlist_spot(&tier_sym, &waiter), None);
+    assert_eq!(client.list_tiers().get(0).unwrap().1.reserved, 0);
+    // Deposit returned, ticket paid for
+    assert_eq!(payment.balance(&waiter), 195);
+}
+
+#[test]
+fn test_waitlist_offer_rolls_when_unclaimed() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let first = Address::generate(&e);
+    let second = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &holder, 1_000);
+    payment.transfer(&holder, &first, &300);
+    payment.transfer(&holder, &second, &300);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &1,
+        &PricingStrategy::Standard,
+    );
+    client.set_waitlist_config(
+        &tier_sym,
+        &WaitlistConfig {
+            deposit_token: Some(payment.address.clone()),
+            deposit: 10,
+            offer_window: 600,
+        },
+    );
+
+    client.purchase(&holder, &payment.address, &tier_sym, &100, &None);
+    client.join_waitlist(&first, &tier_sym);
+    client.join_waitlist(&second, &tier_sym);
+    client.refund(&holder, &payment.address, &0);
+
+    // Nothing lapses while the offer is live
+    assert_eq!(client.roll_waitlist(&tier_sym), 0);
+
+    e.ledger().set_timestamp(601);
+    assert!(client
+        .try_claim_waitlist_offer(&first, &payment.address, &tier_sym, &100)
+        .is_err());
+    assert_eq!(client.roll_waitlist(&tier_sym), 1);
+    assert_eq!(client.get_waitlist_spot(&tier_sym, &first), None);
+    assert_eq!(
+        client
+            .get_waitlist_spot(&tier_sym, &second)
+            .unwrap()
+            .offer_expires_at,
+        1201
+    );
+    assert_eq!(client.list_tiers().get(0).unwrap().1.reserved, 1);
+    // The lapsed deposit is forfeited to the organizer
+    assert_eq!(payment.balance(&first), 290);
+    assert_eq!(client.get_escrow_totals(&payment.address).retained, 10);
+
+    // Declining with nobody left in line puts the seat back on sale
+    client.leave_waitlist(&second, &tier_sym);
+    assert_eq!(payment.balance(&second), 300);
+    assert_eq!(client.list_tiers().get(0).unwrap().1.reserved, 0);
+    client.purchase(&holder, &payment.address, &tier_sym, &105, &None);
+    assert_eq!(client.owner_of(&1), holder);
+}
+
+#[test]
+fn test_waitlist_leave_unlinks_entry() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &holder, 1_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &1,
+        &PricingStrategy::Standard,
+    );
+    client.purchase(&holder, &payment.address, &tier_sym, &100, &None);
+
+    let first = Address::generate(&e);
+    let middle = Address::generate(&e);
+    let last = Address::generate(&e);
+    client.join_waitlist(&first, &tier_sym);
+    client.join_waitlist(&middle, &tier_sym);
+    client.join_waitlist(&last, &tier_sym);
+
+    // Departures leave the line at once, from the front or the middle, and
+    // can rejoin at the back
+    client.leave_waitlist(&first, &tier_sym);
+    client.leave_waitlist(&middle, &tier_sym);
+    assert_eq!(client.waitlist_length(&tier_sym), 1);
+    client.join_waitlist(&first, &tier_sym);
+    assert_eq!(client.waitlist_length(&tier_sym), 2);
+
+    client.refund(&holder, &payment.address, &0);
+    assert_eq!(
+        client
+            .get_waitlist_spot(&tier_sym, &last)
+            .unwrap()
+            .offer_expires_at,
+        3600
+    );
+    assert_eq!(client.get_waitlist_spot(&tier_sym, &middle), None);
+    assert_eq!(client.waitlist_length(&tier_sym), 1);
+
+    // Declining passes the seat to the rejoined address
+    client.leave_waitlist(&last, &tier_sym);
+    assert_eq!(
+        client
+            .get_waitlist_spot(&tier_sym, &first)
+            .unwrap()
+            .offer_expires_at,
+        3600
+    );
+    assert_eq!(client.waitlist_length(&tier_sym), 0);
+}
+
+#[test]
+fn test_waitlist_lapsed_leave_forfeits_and_offer_outlives_sale_window() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let first = Address::generate(&e);
+    let second = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &holder, 1_000);
+    payment.transfer(&holder, &first, &300);
+    payment.transfer(&holder, &second, &300);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &1,
+        &PricingStrategy::Standard,
+    );
+    client.set_waitlist_config(
+        &tier_sym,
+        &WaitlistConfig {
+            deposit_token: Some(payment.address.clone()),
+            deposit: 10,
+            offer_window: 600,
+        },
+    );
+    client.set_sale_window(&tier_sym, &0, &500);
+
+    client.purchase(&holder, &payment.address, &tier_sym, &100, &None);
+    client.join_waitlist(&first, &tier_sym);
+    client.join_waitlist(&second, &tier_sym);
+    client.refund(&holder, &payment.address, &0);
+
+    // Leaving after the offer lapsed, before anyone rolled the waitlist,
+    // still forfeits the deposit
+    e.ledger().set_timestamp(601);
+    client.leave_waitlist(&first, &tier_sym);
+    assert_eq!(payment.balance(&first), 290);
+    assert_eq!(client.get_escrow_totals(&payment.address).retained, 10);
+
+    // The rolled offer can be claimed although public sales have ended
+    assert!(client
+        .try_purchase(&holder, &payment.address, &tier_sym, &100, &None)
+        .is_err());
+    client.claim_waitlist_offer(&second, &payment.address, &tier_sym, &105);
+    assert_eq!(client.owner_of(&1), second);
+    assert_eq!(payment.balance(&second), 195);
+
+    // The refund freed the seat without rewinding the tier's sales count
+    assert_eq!(client.list_tiers().get(0).unwrap().1.minted, 2);
+}
+
+#[test]
+fn test_lottery_draw_for_oversubscribed_tier() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let funder = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &funder, 1_000);
+
+    let tier_sym = Symbol::new(&e, "HYPE");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Hyped"),
+        &100,
+        &2,
+        &PricingStrategy::Standard,
+    );
+    client.set_lottery(&tier_sym, &payment.address, &150, &10, &100, &600);
+
+    let entrants = [
+        Address::generate(&e),
+        Address::generate(&e),
+        Address::generate(&e),
+    ];
+    for entrant in entrants.iter() {
+        payment.transfer(&funder, entrant, &150);
+    }
+
+    // Entries only while the window is open
+    assert!(client.try_enter_lottery(&entrants[0], &tier_sym).is_err());
+    e.ledger().set_timestamp(10);
+    for entrant in entrants.iter() {
+        client.enter_lottery(entrant, &tier_sym);
+    }
+    assert!(client.try_enter_lottery(&entrants[0], &tier_sym).is_err());
+    assert_eq!(payment.balance(&client.address), 450);
+
+    // Public sale is closed and the draw waits for the window to end
+    assert!(client
+        .try_purchase(&funder, &payment.address, &tier_sym, &200, &None)
+        .is_err());
+    assert!(client.try_settle_lottery(&tier_sym, &10).is_err());
+
+    e.ledger().set_timestamp(100);
+    assert_eq!(client.settle_lottery(&tier_sym, &10), 2);
+    assert!(client.try_settle_lottery(&tier_sym, &10).is_err());
+
+    let mut winners = 0;
+    for entrant in entrants.iter() {
+        if client.claim_lottery(entrant, &tier_sym) {
+            winners += 1;
+            assert_eq!(client.balance(entrant), 1);
+            assert_eq!(payment.balance(entrant), 0);
+        } else {
+            assert_eq!(client.balance(entrant), 0);
+            assert_eq!(payment.balance(entrant), 150);
+        }
+        assert_eq!(client.lottery_entry(&tier_sym, entrant), None);
+    }
+    assert_eq!(winners, 2);
+    assert_eq!(client.get_escrow_totals(&payment.address).collected, 300);
+    assert_eq!(client.get_ticket(&1).price_paid, 150);
+    assert!(client
+        .try_purchase(&funder, &payment.address, &tier_sym, &200, &None)
+        .is_err());
+}
+
+#[test]
+fn test_lottery_batched_draw_wallet_cap_and_claim_deadline() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let holder = Address::generate(&e);
+    let prompt = Address::generate(&e);
+    let late = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &holder, 1_000);
+    payment.transfer(&holder, &prompt, &150);
+    payment.transfer(&holder, &late, &150);
+
+    let tier_sym = Symbol::new(&e, "HYPE");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Hyped"),
+        &100,
+        &4,
+        &PricingStrategy::Standard,
+    );
+    client.set_wallet_cap(&tier_sym, &1, &false);
+    client.purchase(&holder, &payment.address, &tier_sym, &100, &None);
+    client.set_lottery(&tier_sym, &payment.address, &150, &10, &100, &600);
+
+    e.ledger().set_timestamp(10);
+    client.enter_lottery(&holder, &tier_sym);
+    client.enter_lottery(&prompt, &tier_sym);
+    client.enter_lottery(&late, &tier_sym);
+    assert_eq!(client.get_lottery(&tier_sym).unwrap().entrants, 3);
+
+    // Three seats left for three entrants, drawn over two calls
+    e.ledger().set_timestamp(100);
+    assert_eq!(client.settle_lottery(&tier_sym, &1), 1);
+    assert!(client.try_claim_lottery(&prompt, &tier_sym).is_err());
+    assert_eq!(client.settle_lottery(&tier_sym, &5), 2);
+    assert_eq!(client.get_lottery(&tier_sym).unwrap().claim_deadline, 700);
+    assert_eq!(client.list_tiers().get(0).unwrap().1.reserved, 3);
+
+    // Winning does not lift the per-wallet cap
+    assert!(client.try_claim_lottery(&holder, &tier_sym).is_err());
+    assert!(client.claim_lottery(&prompt, &tier_sym));
+    assert_eq!(client.balance(&prompt), 1);
+
+    // Unclaimed seats are released once the claim window closes
+    assert!(client.try_release_lottery_seats(&tier_sym).is_err());
+    e.ledger().set_timestamp(701);
+    assert!(!client.claim_lottery(&late, &tier_sym));
+    assert_eq!(payment.balance(&late), 150);
+    assert_eq!(client.release_lottery_seats(&tier_sym), 2);
+    assert_eq!(client.release_lottery_seats(&tier_sym), 0);
+    assert_eq!(client.list_tiers().get(0).unwrap().1.reserved, 0);
+    assert!(!client.claim_lottery(&holder, &tier_sym));
+    client.purchase(&late, &payment.address, &tier_sym, &150, &None);
+    assert_eq!(client.balance(&late), 1);
+}
+
+#[test]
+fn test_auction_clearing_ignores_discounts_and_other_variants() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let early = Address::generate(&e);
+    let promo = Address::generate(&e);
+    let control = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &early, 4_000);
+    payment.transfer(&early, &promo, &1_000);
+    payment.transfer(&early, &control, &1_100);
+
+    let tier_sym = Symbol::new(&e, "AUC");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Auction"),
+        &1000,
+        &3,
+        &PricingStrategy::Standard,
+    );
+    client.set_dutch_auction(
+        &tier_sym,
+        &DutchAuction {
+            start_price: 1000,
+            reserve_price: 400,
+            start_time: 0,
+            end_time: 600,
+            step_seconds: 100,
+            uniform_clearing: true,
+            clearing_price: 0,
+        },
+    );
+    let code = Bytes::from_slice(&e, b"HALF");
+    let code_hash: BytesN<32> = e.crypto().sha256(&code).into();
+    client.add_promo_code(
+        &code_hash,
+        &Discount::Percent(5000),
+        &1,
+        &1_000,
+        &Some(tier_sym.clone()),
+    );
+
+    client.purchase(&early, &payment.address, &tier_sym, &1000, &None);
+
+    // A discounted sale clears at its auction step, not at what was paid
+    e.ledger().set_timestamp(250);
+    client.purchase(&promo, &payment.address, &tier_sym, &400, &Some(code));
+    assert_eq!(client.get_ticket(&1).price_paid, 400);
+    assert_eq!(
+        client.get_dutch_auction(&tier_sym).unwrap().clearing_price,
+        800
+    );
+
+    // Buyers priced by a non-auction variant do not move the clearing price
+    client.set_experiment(
+        &tier_sym,
+        &1,
+        &vec![
+            &e,
+            ExperimentVariant {
+                strategy: PricingStrategy::Standard,
+                weight: 1,
+                sales: 0,
+                revenue: Map::new(&e),
+            },
+        ],
+    );
+    e.ledger().set_timestamp(450);
+    client.purchase(&control, &payment.address, &tier_sym, &1100, &None);
+    assert_eq!(
+        client.get_dutch_auction(&tier_sym).unwrap().clearing_price,
+        800
+    );
+
+    assert_eq!(client.claim_auction_rebate(&early, &0), 200);
+    assert!(client.try_claim_auction_rebate(&promo, &1).is_err());
+    assert!(client.try_claim_auction_rebate(&control, &2).is_err());
+}
+
+#[test]
+fn test_auction_rebate_in_paid_unit_and_only_for_auction_sales() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let early = Address::generate(&e);
+    let middle = Address::generate(&e);
+    let waiter = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &early, 4_000);
+    payment.transfer(&early, &middle, &1_000);
+    payment.transfer(&early, &waiter, &1_000);
+
+    // MockOracle quotes $1.10 against a $1.00 reference: a 1.1x multiplier
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: e.register(MockOracle, ()),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    });
+
+    let tier_sym = Symbol::new(&e, "AUC");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Auction"),
+        &1000,
+        &2,
+        &PricingStrategy::Standard,
+    );
+    client.set_dutch_auction(
+        &tier_sym,
+        &DutchAuction {
+            start_price: 1000,
+            reserve_price: 400,
+            start_time: 0,
+            end_time: 600,
+            step_seconds: 100,
+            uniform_clearing: true,
+            clearing_price: 0,
+        },
+    );
+
+    client.purchase(&early, &payment.address, &tier_sym, &1100, &None);
+    e.ledger().set_timestamp(250);
+    client.purchase(&middle, &payment.address, &tier_sym, &880, &None);
+    assert_eq!(client.get_ticket(&1).price_paid, 880);
+
+    // A seat resold through the waitlist at a lower step does not set the
+    // clearing price, and earns no rebate
+    client.join_waitlist(&waiter, &tier_sym);
+    e.ledger().set_timestamp(300);
+    client.refund(&middle, &payment.address, &1);
+    client.claim_waitlist_offer(&waiter, &payment.address, &tier_sym, &770);
+    assert_eq!(
+        client.get_dutch_auction(&tier_sym).unwrap().clearing_price,
+        800
+    );
+    assert!(client.try_claim_auction_rebate(&waiter, &2).is_err());
+
+    // 1100 paid at the 1000 step clears at 880 in the paid unit
+    assert_eq!(client.claim_auction_rebate(&early, &0), 220);
+    assert_eq!(client.get_ticket(&0).price_paid, 880);
+    assert!(client.try_claim_auction_rebate(&early, &0).is_err());
+}
+
+#[test]
+fn test_dutch_auction_steps_and_uniform_clearing() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let early = Address::generate(&e);
+    let middle = Address::generate(&e);
+    let late = Address::generate(&e);
+    let contract_id = e.register(SoulboundTicketContract, ());
+    let client = SoulboundTicketContractClient::new(&e, &contract_id);
+    client.initialize(
+        &admin,
+        &Address::generate(&e),
+        &String::from_str(&e, "EventTicket"),
+        &String::from_str(&e, "TKT"),
+        &String::from_str(&e, "https://example.com"),
+        &10_000,
+        &10_000,
+    );
+    let payment = create_payment_token(&e, &early, 3_000);
+    payment.transfer(&early, &middle, &1_000);
+    payment.transfer(&early, &late, &1_000);
+
+    let tier_sym = Symbol::new(&e, "AUC");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Auction"),
+        &1000,
+        &3,
+        &PricingStrategy::Standard,
+    );
+    let auction = DutchAuction {
+        start_price: 1000,
+        reserve_price: 400,
+        start_time: 0,
+        end_time: 600,
+        step_seconds: 100,
+        uniform_clearing: true,
+        clearing_price: 0,
+    };
+    // Rebates have to settle before the event starts
+    assert!(client
+        .try_set_dutch_auction(
+            &tier_sym,
+            &DutchAuction {
+                end_time: 10_001,
+                ..auction.clone()
+            }
+        )
+        .is_err());
+    client.set_dutch_auction(&tier_sym, &auction);
+
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+    client.purchase(&early, &payment.address, &tier_sym, &1000, &None);
+
+    // Two full steps of 100 each
+    e.ledger().set_timestamp(250);
+    assert_eq!(client.get_ticket_price(&tier_sym), 800);
+    client.purchase(&middle, &payment.address, &tier_sym, &800, &None);
+    assert!(client.try_claim_auction_rebate(&early, &0).is_err());
+
+    e.ledger().set_timestamp(700);
+    assert_eq!(client.get_ticket_price(&tier_sym), 400);
+    client.purchase(&late, &payment.address, &tier_sym, &400, &None);
+    assert_eq!(
+        client.get_dutch_auction(&tier_sym).unwrap().clearing_price,
+        400
+    );
+
+    assert_eq!(client.claim_auction_rebate(&early, &0), 600);
+    assert_eq!(client.claim_auction_rebate(&middle, &1), 400);
+    assert!(client.try_claim_auction_rebate(&early, &0).is_err());
+    assert!(client.try_claim_auction_rebate(&late, &2).is_err());
+    assert_eq!(payment.balance(&early), 600);
+    assert_eq!(client.get_ticket(&0).price_paid, 400);
+    assert_eq!(client.outstanding(&payment.address), 1200);
+}
+
+#[test]
+fn test_bonding_curves() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+
+    let linear = Symbol::new(&e, "LIN");
+    let expo = Symbol::new(&e, "EXP");
+    let step = Symbol::new(&e, "STEP");
+    for tier_sym in [&linear, &expo, &step] {
+        client.add_tier(
+            tier_sym,
+            &String::from_str(&e, "Curve"),
+            &100,
+            &100,
+            &PricingStrategy::Standard,
+        );
+    }
+    client.set_bonding_curve(&linear, &BondingCurve::Linear(10));
+    client.set_bonding_curve(&expo, &BondingCurve::Exponential(1000));
+    client.set_bonding_curve(&step, &BondingCurve::Step(3, 25));
+    assert!(client
+        .try_set_bonding_curve(&step, &BondingCurve::Step(0, 25))
+        .is_err());
+
+    // Linear: 100, 110, 120, ...
+    assert_eq!(client.get_ticket_price(&linear), 100);
+    assert_eq!(client.quote_total_price(&linear, &3), 330);
+    client.purchase_many(&buyer, &payment.address, &linear, &3, &330);
+    assert_eq!(client.get_ticket_price(&linear), 130);
+    assert_eq!(payment.balance(&buyer), 670);
+
+    // Exponential: 100 * 1.1^n
+    client.batch_mint(&buyer, &expo, &3);
+    assert_eq!(client.get_ticket_price(&expo), 133);
+    assert_eq!(client.quote_total_price(&expo, &2), 133 + 146);
+
+    // Step: +25 every third ticket
+    client.batch_mint(&buyer, &step, &2);
+    assert_eq!(client.get_ticket_price(&step), 100);
+    client.batch_mint(&buyer, &step, &1);
+    assert_eq!(client.get_ticket_price(&step), 125);
+}
+
+#[test]
+#[should_panic(expected = "Price overflow")]
+fn test_bonding_curve_overflow_is_checked() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "EXP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Doubling"),
+        &(i128::MAX / 4),
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_bonding_curve(&tier_sym, &BondingCurve::Exponential(10000));
+    client.batch_mint(&admin, &tier_sym, &3);
+    client.get_ticket_price(&tier_sym);
+}
+
+#[test]
+fn test_experiment_buckets_buyers_and_tracks_conversions() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let funder = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let payment = create_payment_token(&e, &funder, 10_000);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.set_experiment(
+        &tier_sym,
+        &7,
+        &vec![
+            &e,
+            ExperimentVariant {
+                strategy: PricingStrategy::Standard,
+                weight: 1,
+                sales: 0,
+                revenue: Map::new(&e),
+            },
+            ExperimentVariant {
+                strategy: PricingStrategy::AbTestB,
+                weight: 1,
+                sales: 5,
+                revenue: map![&e, (payment.address.clone(), 500)],
+            },
+        ],
+    );
+    assert!(client
+        .try_set_experiment(&tier_sym, &8, &Vec::new(&e))
+        .is_err());
+
+    // Find a buyer in each bucket
+    let mut control = None;
+    let mut treated = None;
+    while control.is_none() || treated.is_none() {
+        let buyer = Address::generate(&e);
+        match client.experiment_variant(&tier_sym, &buyer) {
+            Some(0) => control = Some(buyer),
+            Some(1) => treated = Some(buyer),
+            _ => panic!("unexpected bucket"),
+        }
+    }
+    let control = control.unwrap();
+    let treated = treated.unwrap();
+    assert_eq!(client.experiment_variant(&tier_sym, &treated), Some(1));
+
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+    assert_eq!(client.get_ticket_price_for(&control, &tier_sym), 100);
+    assert_eq!(client.get_ticket_price_for(&treated, &tier_sym), 120);
+
+    payment.transfer(&funder, &control, &1_000);
+    payment.transfer(&funder, &treated, &1_000);
+    client.purchase(&control, &payment.address, &tier_sym, &100, &None);
+    assert!(client
+        .try_purchase(&treated, &payment.address, &tier_sym, &100, &None)
+        .is_err());
+    client.purchase_many(&treated, &payment.address, &tier_sym, &2, &240);
+
+    let experiment = client.get_experiment(&tier_sym).unwrap();
+    let variant_a = experiment.variants.get(0).unwrap();
+    let variant_b = experiment.variants.get(1).unwrap();
+    assert_eq!(variant_a.sales, 1);
+    assert_eq!(variant_a.revenue, map![&e, (payment.address.clone(), 100)]);
+    assert_eq!(variant_b.sales, 2);
+    assert_eq!(variant_b.revenue, map![&e, (payment.address.clone(), 240)]);
+
+    // Revenue is kept apart per payment token, and refunds are taken out
+    let other = create_payment_token(&e, &funder, 1_000);
+    other.transfer(&funder, &control, &100);
+    client.purchase(&control, &other.address, &tier_sym, &100, &None);
+    client.refund(&treated, &payment.address, &2);
+    let experiment = client.get_experiment(&tier_sym).unwrap();
+    let variant_a = experiment.variants.get(0).unwrap();
+    let variant_b = experiment.variants.get(1).unwrap();
+    assert_eq!(variant_a.sales, 2);
+    assert_eq!(
+        variant_a.revenue,
+        map![
+            &e,
+            (payment.address.clone(), 100),
+            (other.address.clone(), 100)
+        ]
+    );
+    assert_eq!(variant_b.sales, 1);
+    assert_eq!(variant_b.revenue, map![&e, (payment.address.clone(), 120)]);
+
+    client.end_experiment(&tier_sym);
+    assert_eq!(client.get_ticket_price_for(&treated, &tier_sym), 100);
+
+    // An ID runs once per tier; refunds of its sales leave a later
+    // experiment's counters alone
+    let single = vec![
+        &e,
+        ExperimentVariant {
+            strategy: PricingStrategy::Standard,
+            weight: 1,
+            sales: 0,
+            revenue: Map::new(&e),
+        },
+    ];
+    assert!(client.try_set_experiment(&tier_sym, &7, &single).is_err());
+    client.set_experiment(&tier_sym, &8, &single);
+    client.refund(&treated, &payment.address, &1);
+    client.refund(&control, &payment.address, &0);
+    let variant = client
+        .get_experiment(&tier_sym)
+        .unwrap()
+        .variants
+        .get(0)
+        .unwrap();
+    assert_eq!(variant.sales, 0);
+    assert_eq!(variant.revenue, Map::new(&e));
+}
+
+#[test]
+fn test_oracle_cached_per_update_frequency_and_time_weighted() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let oracle_id = e.register(MockSettableOracle, ());
+    let oracle_client = MockSettableOracleClient::new(&e, &oracle_id);
+    oracle_client.set_price(&oracle::DIA_ORACLE_DECIMALS);
+
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: oracle_id.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 100,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    });
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    assert_eq!(client.refresh_oracle_price(), Some(10_000));
+
+    // The feed doubles, but the cached sample holds until the next refresh
+    oracle_client.set_price(&(2 * oracle::DIA_ORACLE_DECIMALS));
+    e.ledger().set_timestamp(50);
+    assert_eq!(client.refresh_oracle_price(), Some(10_000));
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+
+    // A fresh sample carries no weight until time passes
+    e.ledger().set_timestamp(100);
+    assert_eq!(client.refresh_oracle_price(), Some(10_000));
+    assert_eq!(client.oracle_observations().len(), 2);
+
+    e.ledger().set_timestamp(150);
+    // (100s at 1.0x + 50s at 2.0x) / 150s
+    assert_eq!(client.refresh_oracle_price(), Some(13_333));
+    assert_eq!(client.get_ticket_price(&tier_sym), 133);
+
+    // Only the most recent samples are kept
+    for i in 2..12u64 {
+        e.ledger().set_timestamp(i * 100);
+        client.refresh_oracle_price();
+    }
+    let observations = client.oracle_observations();
+    assert_eq!(observations.len(), 8);
+    assert_eq!(observations.first().unwrap().timestamp, 400);
+    assert_eq!(client.refresh_oracle_price(), Some(20_000));
+}
+
+#[test]
+fn test_price_views_are_read_only_and_samples_expire() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let oracle_id = e.register(MockSettableOracle, ());
+    let oracle_client = MockSettableOracleClient::new(&e, &oracle_id);
+    oracle_client.set_price(&(2 * oracle::DIA_ORACLE_DECIMALS));
+
+    let config = PricingConfig {
+        oracle_address: oracle_id.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 100,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 300,
+    };
+    assert!(client
+        .try_set_pricing_config(&PricingConfig {
+            twap_window_seconds: 0,
+            ..config.clone()
+        })
+        .is_err());
+    client.set_pricing_config(&config);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    // Quoting sees the fresh sample without storing it
+    assert_eq!(client.get_ticket_price(&tier_sym), 200);
+    assert_eq!(client.quote_total_price(&tier_sym, &2), 400);
+    assert_eq!(client.oracle_observations().len(), 0);
+    assert_eq!(client.price_health().source, PriceSource::Neutral);
+
+    // Purchases record it
+    let buyer = Address::generate(&e);
+    let payment = create_payment_token(&e, &buyer, 1_000);
+    client.purchase(&buyer, &payment.address, &tier_sym, &200, &None);
+    assert_eq!(client.oracle_observations().len(), 1);
+
+    // With the feed down the last sample only counts within the window
+    oracle_client.set_offline(&true);
+    e.ledger().set_timestamp(300);
+    assert_eq!(client.get_ticket_price(&tier_sym), 200);
+    e.ledger().set_timestamp(301);
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+    assert_eq!(client.refresh_oracle_price(), Some(10_000));
+    assert_eq!(client.oracle_observations().len(), 0);
+}
+
+#[test]
+fn test_median_of_oracle_sources_with_quorum_and_deviation() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let dia_id = e.register(MockOracle, ()); // $1.10
+    let dex_id = e.register(MockDex, ()); // $1.05
+    let settable_id = e.register(MockSettableOracle, ());
+    let settable = MockSettableOracleClient::new(&e, &settable_id);
+    settable.set_price(&oracle::DIA_ORACLE_DECIMALS); // $1.00
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    let config = PricingConfig {
+        oracle_address: admin.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: vec![
+            &e,
+            OracleSource {
+                address: dia_id.clone(),
+                adapter: OracleAdapter::Dia,
+            },
+            OracleSource {
+                address: dex_id.clone(),
+                adapter: OracleAdapter::DexRouter,
+            },
+            OracleSource {
+                address: settable_id.clone(),
+                adapter: OracleAdapter::Dia,
+            },
+        ],
+        min_quorum: 2,
+        max_deviation_bps: 1500,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    };
+    client.set_pricing_config(&config);
+
+    // Median of $1.00, $1.05 and $1.10
+    assert_eq!(client.get_ticket_price(&tier_sym), 105);
+
+    // One feed running away moves the median, but the spread trips the guard
+    // and pricing holds at the tier's current price
+    settable.set_price(&(2 * oracle::DIA_ORACLE_DECIMALS));
+    assert_eq!(client.refresh_oracle_price(), None);
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+
+    // Without the deviation guard the median wins over the outlier
+    client.set_pricing_config(&PricingConfig {
+        max_deviation_bps: 0,
+        ..config.clone()
+    });
+    assert_eq!(client.get_ticket_price(&tier_sym), 110);
+
+    // Below quorum: only one of the two sources answers
+    client.set_pricing_config(&PricingConfig {
+        oracle_sources: vec![
+            &e,
+            OracleSource {
+                address: dia_id.clone(),
+                adapter: OracleAdapter::Dia,
+            },
+            OracleSource {
+                address: admin.clone(),
+                adapter: OracleAdapter::Dia,
+            },
+        ],
+        ..config.clone()
+    });
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+
+    assert!(client
+        .try_set_pricing_config(&PricingConfig {
+            min_quorum: 4,
+            ..config
+        })
+        .is_err());
+}
+
+#[test]
+fn test_sep40_oracle_adapter() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let sep40_id = e.register(MockSep40Oracle, ());
+    let dia_id = e.register(MockOracle, ());
+    let dex_id = e.register(MockDex, ());
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    // $1.20 at 14 decimals is normalized to DIA's 8
+    let config = PricingConfig {
+        oracle_address: sep40_id.clone(),
+        oracle_adapter: OracleAdapter::Sep40,
+        dex_pool_address: dex_id.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    };
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 120);
+
+    // An asset the feed doesn't know falls back to the DEX ($1.05)
+    client.set_pricing_config(&PricingConfig {
+        oracle_pair: String::from_str(&e, "BTC/USD"),
+        ..config.clone()
+    });
+    assert_eq!(client.get_ticket_price(&tier_sym), 105);
+
+    // SEP-40 and DIA feeds side by side: median of $1.05, $1.10 and $1.20
+    client.set_pricing_config(&PricingConfig {
+        oracle_sources: vec![
+            &e,
+            OracleSource {
+                address: sep40_id.clone(),
+                adapter: OracleAdapter::Sep40,
+            },
+            OracleSource {
+                address: dia_id.clone(),
+                adapter: OracleAdapter::Dia,
+            },
+            OracleSource {
+                address: dex_id.clone(),
+                adapter: OracleAdapter::DexRouter,
+            },
+        ],
+        min_quorum: 3,
+        ..config
+    });
+    assert_eq!(client.get_ticket_price(&tier_sym), 110);
+}
+
+#[test]
+fn test_price_health_and_circuit_breaker() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let oracle_id = e.register(MockSettableOracle, ());
+    let oracle_client = MockSettableOracleClient::new(&e, &oracle_id);
+    let dex_id = e.register(MockDex, ());
+    oracle_client.set_price(&oracle::DIA_ORACLE_DECIMALS);
+
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    assert_eq!(client.price_health().source, PriceSource::Neutral);
+
+    // Primary oracle down: the DEX price is used and reported as such
+    let config = PricingConfig {
+        oracle_address: admin.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: dex_id.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 100,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 2000,
+        twap_window_seconds: 3600,
+    };
+    client.set_pricing_config(&config);
+    client.refresh_oracle_price();
+    e.ledger().set_timestamp(50);
+    let health = client.price_health();
+    assert_eq!(health.source, PriceSource::DexFallback);
+    assert_eq!(health.age_seconds, 50);
+    assert_eq!(health.last_multiplier, 10_500);
+
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: oracle_id.clone(),
+        update_frequency: 0,
+        ..config
+    });
+    // Price views never record samples; refreshing does
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+    assert_eq!(client.price_health().source, PriceSource::DexFallback);
+    client.refresh_oracle_price();
+    assert_eq!(client.price_health().source, PriceSource::Primary);
+
+    // A 10% move is within the 20% breaker
+    e.ledger().set_timestamp(60);
+    oracle_client.set_price(&110_000_000);
+    client.refresh_oracle_price();
+    assert!(!client.price_health().is_frozen);
+
+    // Doubling trips it: pricing freezes at the current price
+    e.ledger().set_timestamp(70);
+    oracle_client.set_price(&(2 * oracle::DIA_ORACLE_DECIMALS));
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+    assert!(!client.price_health().is_frozen);
+    assert_eq!(client.refresh_oracle_price(), None);
+    let health = client.price_health();
+    assert!(health.is_frozen);
+    assert_eq!(health.last_multiplier, 20_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+    // The spike that tripped the breaker is not averaged in
+    let observations = client.oracle_observations();
+    assert_eq!(observations.last().unwrap().multiplier, 11_000);
+
+    // Once the admin accepts the new level, pricing follows the oracle again
+    client.emergency_freeze(&false);
+    assert_eq!(client.oracle_observations().len(), 0);
+    e.ledger().set_timestamp(80);
+    assert_eq!(client.get_ticket_price(&tier_sym), 200);
+    assert!(!client.price_health().is_frozen);
+}
+
+#[test]
+fn test_fiat_tier_settled_in_payment_token() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let oracle_id = e.register(MockOracle, ()); // XLM at $1.10
+    let xlm = create_payment_token(&e, &buyer, 1_000_000_000);
+    let usdc = create_payment_token(&e, &buyer, 1_000_000_000);
+    let other = create_payment_token(&e, &buyer, 1_000_000_000);
+
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: oracle_id.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    });
+
+    // $49.00
+    let tier_sym = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "VIP"),
+        &4900,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    let usd = Symbol::new(&e, "USD");
+    client.set_tier_currency(&tier_sym, &Some(usd.clone()));
+    client.set_accepted_token(
+        &xlm.address,
+        &AcceptedToken {
+            currency: usd.clone(),
+            pair: Some(String::from_str(&e, "XLM/USD")),
+        },
+    );
+    client.set_accepted_token(
+        &usdc.address,
+        &AcceptedToken {
+            currency: usd.clone(),
+            pair: None,
+        },
+    );
+
+    // The oracle moves the conversion rate, not the fiat price
+    assert_eq!(client.get_ticket_price(&tier_sym), 4900);
+    // 49.00 / 1.10 XLM at 7 decimals, rounded up
+    assert_eq!(client.quote_in_token(&tier_sym, &xlm.address), 445_454_546);
+    assert_eq!(client.quote_in_token(&tier_sym, &usdc.address), 490_000_000);
+
+    // Slippage limits are in the token actually paid, not in cents
+    assert!(client
+        .try_purchase(&buyer, &xlm.address, &tier_sym, &4900, &None)
+        .is_err());
+    client.purchase(&buyer, &xlm.address, &tier_sym, &445_454_546, &None);
+    assert_eq!(xlm.balance(&buyer), 1_000_000_000 - 445_454_546);
+    assert_eq!(client.get_ticket(&0).price_paid, 445_454_546);
+
+    // Quotes lock the token amount for the token they were made in
+    let quote = client.quote_price(&buyer, &tier_sym, &usdc.address);
+    assert_eq!((quote.price, quote.amount), (4900, 490_000_000));
+    assert!(client
+        .try_purchase_with_quote(&buyer, &xlm.address, &tier_sym)
+        .is_err());
+    client.purchase_with_quote(&buyer, &usdc.address, &tier_sym);
+    assert_eq!(usdc.balance(&buyer), 510_000_000);
+    assert!(client
+        .try_purchase(&buyer, &other.address, &tier_sym, &i128::MAX, &None)
+        .is_err());
+
+    // Refunds pay back what was actually paid, in the token it was paid in
+    client.refund(&buyer, &xlm.address, &0);
+    assert_eq!(xlm.balance(&buyer), 1_000_000_000);
+    assert!(client.try_set_tier_currency(&tier_sym, &None).is_err());
+
+    // Bounds are per tier and in the tier's own unit
+    client.set_tier_price_bounds(
+        &tier_sym,
+        &Some(PriceBounds {
+            floor: 5000,
+            ceiling: 10_000,
+        }),
+    );
+    assert_eq!(client.get_ticket_price(&tier_sym), 5000);
+    assert_eq!(client.quote_in_token(&tier_sym, &usdc.address), 500_000_000);
+    assert!(client
+        .try_set_tier_price_bounds(
+            &tier_sym,
+            &Some(PriceBounds {
+                floor: 10_000,
+                ceiling: 5000,
+            }),
+        )
+        .is_err());
+
+    // Uniform clearing rebates are in token units, so fiat tiers cannot use it
+    let auction = DutchAuction {
+        start_price: 1000,
+        reserve_price: 400,
+        start_time: 0,
+        end_time: 600,
+        step_seconds: 100,
+        uniform_clearing: true,
+        clearing_price: 0,
+    };
+    assert!(client.try_set_dutch_auction(&tier_sym, &auction).is_err());
+    let gen = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &gen,
+        &String::from_str(&e, "General"),
+        &1000,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.set_dutch_auction(&gen, &auction);
+    assert!(client.try_set_tier_currency(&gen, &Some(usd)).is_err());
+}
+
+#[test]
+fn test_fiat_token_rate_goes_through_the_circuit_breaker() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let oracle_id = e.register(MockSettableOracle, ());
+    let oracle_client = MockSettableOracleClient::new(&e, &oracle_id);
+    oracle_client.set_price(&oracle::DIA_ORACLE_DECIMALS); // $1.00
+    let xlm = create_payment_token(&e, &buyer, 1_000_000_000);
+
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: oracle_id.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 1000,
+        twap_window_seconds: 3600,
+    });
+
+    // $10.00
+    let tier_sym = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "VIP"),
+        &1000,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    let usd = Symbol::new(&e, "USD");
+    client.set_tier_currency(&tier_sym, &Some(usd.clone()));
+    client.set_accepted_token(
+        &xlm.address,
+        &AcceptedToken {
+            currency: usd,
+            pair: Some(String::from_str(&e, "XLM/USD")),
+        },
+    );
+
+    client.purchase(&buyer, &xlm.address, &tier_sym, &100_000_000, &None);
+    assert_eq!(xlm.balance(&buyer), 900_000_000);
+
+    // A doubled XLM price trips the breaker instead of halving the charge
+    e.ledger().with_mut(|li| li.timestamp = 10);
+    oracle_client.set_price(&(2 * oracle::DIA_ORACLE_DECIMALS));
+    client.purchase(&buyer, &xlm.address, &tier_sym, &100_000_000, &None);
+    assert_eq!(xlm.balance(&buyer), 800_000_000);
+    assert_eq!(client.quote_in_token(&tier_sym, &xlm.address), 100_000_000);
+
+    // Once unfrozen the rate is sampled afresh
+    e.ledger().with_mut(|li| li.timestamp = 20);
+    client.emergency_freeze(&false);
+    client.purchase(&buyer, &xlm.address, &tier_sym, &50_000_000, &None);
+    assert_eq!(xlm.balance(&buyer), 750_000_000);
+}
+
+#[test]
+fn test_fiat_rate_holds_through_a_freeze_past_the_twap_window() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let oracle_id = e.register(MockSettableOracle, ());
+    let oracle_client = MockSettableOracleClient::new(&e, &oracle_id);
+    oracle_client.set_price(&oracle::DIA_ORACLE_DECIMALS); // $1.00
+    let xlm = create_payment_token(&e, &buyer, 1_000_000_000);
+
+    client.set_pricing_config(&PricingConfig {
+        oracle_address: oracle_id.clone(),
+        oracle_adapter: OracleAdapter::Dia,
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: 0,
+        is_frozen: false,
+        oracle_pair: String::from_str(&e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: Vec::new(&e),
+        min_quorum: 0,
+        max_deviation_bps: 0,
+        max_multiplier_move_bps: 0,
+        twap_window_seconds: 3600,
+    });
+
+    // $10.00
+    let tier_sym = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "VIP"),
+        &1000,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    let usd = Symbol::new(&e, "USD");
+    client.set_tier_currency(&tier_sym, &Some(usd.clone()));
+    client.set_accepted_token(
+        &xlm.address,
+        &AcceptedToken {
+            currency: usd,
+            pair: Some(String::from_str(&e, "XLM/USD")),
+        },
+    );
+
+    // Frozen before the first fiat sale: the token rate is still seeded once
+    client.emergency_freeze(&true);
+    e.ledger().with_mut(|li| li.timestamp = 100);
+    client.purchase(&buyer, &xlm.address, &tier_sym, &100_000_000, &None);
+    assert_eq!(xlm.balance(&buyer), 900_000_000);
+
+    // Long past the TWAP window the accepted rate still stands, whatever
+    // the oracle says meanwhile
+    e.ledger().with_mut(|li| li.timestamp = 10_000);
+    oracle_client.set_price(&(2 * oracle::DIA_ORACLE_DECIMALS));
+    assert_eq!(client.quote_in_token(&tier_sym, &xlm.address), 100_000_000);
+    client.purchase(&buyer, &xlm.address, &tier_sym, &100_000_000, &None);
+    assert_eq!(xlm.balance(&buyer), 800_000_000);
+
+    // Unfreezing samples the rate afresh
+    client.emergency_freeze(&false);
+    client.purchase(&buyer, &xlm.address, &tier_sym, &50_000_000, &None);
+    assert_eq!(xlm.balance(&buyer), 750_000_000);
+}
+
+#[test]
+fn test_ticket_enumeration_views() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let alice = Address::generate(&e);
+    let bob = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let general = Symbol::new(&e, "GEN");
+    let vip = Symbol::new(&e, "VIP");
+    for tier_sym in [&general, &vip] {
+        client.add_tier(
+            tier_sym,
+            &String::from_str(&e, "Tier"),
+            &0,
+            &100,
+            &PricingStrategy::Standard,
+        );
+    }
+    client.set_transfer_policy(&general, &TransferPolicy::TransferableUntil(1_000));
+
+    client.batch_mint(&alice, &general, &3); // 0, 1, 2
+    client.batch_mint(&bob, &vip, &2); // 3, 4
+    assert_eq!(client.total_supply(), 5);
+    assert_eq!(client.tickets_of(&alice, &0, &10), vec![&e, 0, 1, 2]);
+    assert_eq!(client.tickets_by_tier(&vip, &0, &10), vec![&e, 3, 4]);
+
+    // Moving a ticket re-indexes its holder (the last entry fills the gap)
+    client.transfer(&alice, &bob, &0);
+    assert_eq!(client.tickets_of(&alice, &0, &10), vec![&e, 2, 1]);
+    assert_eq!(client.tickets_of(&bob, &0, &10), vec![&e, 3, 4, 0]);
+    assert_eq!(client.tickets_of(&bob, &1, &2), vec![&e, 4, 0]);
+    assert_eq!(client.tickets_of(&bob, &10, &2), Vec::<u32>::new(&e));
+    // Indexed IDs are the token IDs ownership is tracked under
+    for token_id in client.tickets_of(&bob, &0, &10).iter() {
+        assert_eq!(client.owner_of(&token_id), bob);
+    }
+    for token_id in client.tickets_by_tier(&vip, &0, &10).iter() {
+        assert_eq!(client.get_ticket(&token_id).tier_symbol, vip);
+    }
+
+    // Refunds burn the ticket out of every index
+    client.refund(&bob, &admin, &3);
+    assert_eq!(client.total_supply(), 4);
+    assert_eq!(client.tickets_of(&bob, &0, &10), vec![&e, 0, 4]);
+    assert_eq!(client.tickets_by_tier(&vip, &0, &10), vec![&e, 4]);
+    assert_eq!(client.tickets_by_tier(&general, &0, &10), vec![&e, 0, 1, 2]);
+
+    // Pages are capped
+    for _ in 0..7 {
+        client.batch_mint(&alice, &general, &10);
+    }
+    assert_eq!(client.tickets_of(&alice, &0, &100).len(), 50);
+    let tail = client.tickets_of(&alice, &50, &100);
+    assert_eq!(tail.len(), 22);
+    assert_eq!(tail.get(21), Some(74));
+
+    // Removing from the first bucket pulls the last entry across buckets
+    client.transfer(&alice, &bob, &2);
+    assert_eq!(client.tickets_of(&alice, &0, &1), vec![&e, 74]);
+    assert_eq!(client.tickets_of(&alice, &70, &10).len(), 1);
+}
+
+fn decode_base64(encoded: &str) -> std::string::String {
+    let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut bytes = std::vec::Vec::new();
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in encoded.bytes().filter(|&c| c != b'=') {
+        acc = acc << 6 | alphabet.iter().position(|&a| a == c).unwrap() as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    std::string::String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn test_dynamic_token_uri() {
+    use std::string::ToString;
+
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let alice = Address::generate(&e);
+    let scanner = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let vip = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &vip,
+        &String::from_str(&e, "VIP \"Gold\""),
+        &0,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.batch_mint(&alice, &vip, &2);
+
+    assert_eq!(
+        client.token_metadata(&0),
+        String::from_str(
+            &e,
+            "{\"name\":\"EventTicket #0\",\"external_url\":\"https://example.com0\",\
+             \"attributes\":[{\"trait_type\":\"Tier\",\"value\":\"VIP \\\"Gold\\\"\"},\
+             {\"trait_type\":\"Checked In\",\"value\":\"No\"},\
+             {\"trait_type\":\"Status\",\"value\":\"Valid\"}]}"
+        )
+    );
+
+    // Refunded tickets are burned but still describe themselves
+    let payment = create_payment_token(&e, &alice, 1_000);
+    let general = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &general,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.purchase(&alice, &payment.address, &general, &100, &None);
+    client.refund(&alice, &payment.address, &2);
+    assert_eq!(
+        client.token_metadata(&2),
+        String::from_str(
+            &e,
+            "{\"name\":\"EventTicket #2\",\
+             \"attributes\":[{\"trait_type\":\"Tier\",\"value\":\"General\"},\
+             {\"trait_type\":\"Checked In\",\"value\":\"No\"},\
+             {\"trait_type\":\"Status\",\"value\":\"Invalid\"}]}"
+        )
+    );
+    assert!(client.try_token_uri(&2).is_ok());
+
+    // Names are capped so that fully escaped metadata still fits
+    let long_name = "\"".repeat(65);
+    assert!(client
+        .try_update_tier(&vip, &String::from_str(&e, &long_name), &0, &10)
+        .is_err());
+    client.update_tier(&vip, &String::from_str(&e, &long_name[..64]), &0, &10);
+    client.set_tier_image(&vip, &Some(String::from_str(&e, &"\\\\".repeat(128))));
+    assert!(client.try_token_uri(&1).is_ok());
+    client.update_tier(&vip, &String::from_str(&e, "VIP \"Gold\""), &0, &10);
+    client.set_tier_image(&vip, &None);
+
+    // Organizer sets the tier artwork and a seat; the gate checks it in
+    client.set_tier_image(&vip, &Some(String::from_str(&e, "ipfs://vip.png")));
+    client.assign_seat(&0, &Some(String::from_str(&e, "B-14")));
+    client.add_scanner(&scanner);
+    client.check_in(&scanner, &0);
+    client.cancel_event(&String::from_str(&e, "Storm"));
+
+    let expected = "{\"name\":\"EventTicket #0\",\"image\":\"ipfs://vip.png\",\
+                    \"external_url\":\"https://example.com0\",\
+                    \"attributes\":[{\"trait_type\":\"Tier\",\"value\":\"VIP \\\"Gold\\\"\"},\
+                    {\"trait_type\":\"Seat\",\"value\":\"B-14\"},\
+                    {\"trait_type\":\"Checked In\",\"value\":\"Yes\"},\
+                    {\"trait_type\":\"Status\",\"value\":\"Cancelled\"}]}";
+    assert_eq!(client.token_metadata(&0), String::from_str(&e, expected));
+
+    // token_uri serves the same document as a base64 data URI
+    let uri = client.token_uri(&0).to_string();
+    let encoded = uri.strip_prefix("data:application/json;base64,").unwrap();
+    assert_eq!(decode_base64(encoded), expected);
+
+    // Ticket 1 shares the tier image but has no seat
+    assert!(client
+        .token_metadata(&1)
+        .to_string()
+        .contains("ipfs://vip.png"));
+    assert!(!client.token_metadata(&1).to_string().contains("Seat"));
+
+    // Seat labels are bounded and need a live ticket
+    assert!(client
+        .try_assign_seat(&1, &Some(String::from_str(&e, "")))
+        .is_err());
+    assert!(client
+        .try_assign_seat(&9, &Some(String::from_str(&e, "A-1")))
+        .is_err());
+    assert!(client.try_token_uri(&9).is_err());
+}